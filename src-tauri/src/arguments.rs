@@ -0,0 +1,363 @@
+use crate::config::LauncherConfig;
+use crate::minecraft_api::{Argument, RuleContext, VersionJson};
+use crate::paths::{GameDirectory, CLASSPATH_SEPARATOR};
+
+/// JVM arguments the vanilla launcher uses for versions whose `arguments.jvm` (if any) doesn't set the classpath
+const LEGACY_JVM_ARGUMENTS: &[&str] = &[
+    "-Djava.library.path=${natives_directory}",
+    "-cp",
+    "${classpath}",
+];
+
+/// Values substituted into the `${...}` placeholders of the version JSON
+#[derive(Debug, Clone, Default)]
+pub struct LaunchVariables {
+    pub auth_player_name: String,
+    pub auth_uuid: String,
+    pub auth_access_token: String,
+    pub user_type: String,
+    pub version_name: String,
+    pub version_type: String,
+    pub game_directory: String,
+    pub assets_root: String,
+    /// Same as `assets_root` unless the asset index uses a legacy virtual/resources layout
+    pub game_assets: String,
+    pub assets_index_name: String,
+    pub natives_directory: String,
+    pub library_directory: String,
+    pub classpath: String,
+    pub classpath_separator: String,
+    pub launcher_name: String,
+    pub launcher_version: String,
+}
+
+impl LaunchVariables {
+    /// Variables derived from the launcher configuration; version specific fields are left empty
+    pub fn new(config: &LauncherConfig, game_dir: &GameDirectory) -> Self {
+        Self {
+            auth_player_name: config.player_name.clone(),
+            auth_uuid: config.get_player_uuid(),
+            auth_access_token: "N/A".to_string(),
+            user_type: "legacy".to_string(),
+            game_directory: game_dir.root().to_string_lossy().to_string(),
+            assets_root: game_dir.assets_dir().to_string_lossy().to_string(),
+            game_assets: game_dir.assets_dir().to_string_lossy().to_string(),
+            library_directory: game_dir.libraries_dir().to_string_lossy().to_string(),
+            classpath_separator: CLASSPATH_SEPARATOR.to_string(),
+            launcher_name: "satellite".to_string(),
+            launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Look up the value of a placeholder name
+    pub fn get(&self, name: &str) -> Option<&str> {
+        let value = match name {
+            "auth_player_name" => &self.auth_player_name,
+            "auth_uuid" => &self.auth_uuid,
+            "auth_access_token" | "auth_session" => &self.auth_access_token,
+            "user_type" => &self.user_type,
+            "version_name" => &self.version_name,
+            "version_type" => &self.version_type,
+            "game_directory" => &self.game_directory,
+            "assets_root" => &self.assets_root,
+            "game_assets" => &self.game_assets,
+            "assets_index_name" => &self.assets_index_name,
+            "natives_directory" => &self.natives_directory,
+            "library_directory" => &self.library_directory,
+            "classpath" => &self.classpath,
+            "classpath_separator" => &self.classpath_separator,
+            "launcher_name" => &self.launcher_name,
+            "launcher_version" => &self.launcher_version,
+            "user_properties" => return Some("{}"),
+            "clientid" | "auth_xuid" => return Some(""),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Replace every known `${name}` placeholder in `template`; unknown ones are kept verbatim
+    pub fn substitute(&self, template: &str) -> String {
+        let mut result = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+
+            match after.find('}') {
+                Some(end) => {
+                    let name = &after[..end];
+                    match self.get(name) {
+                        Some(value) => result.push_str(value),
+                        None => result.push_str(&rest[start..start + end + 3]),
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    result.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Expand a list of templated arguments for the given context
+pub fn expand_arguments(
+    arguments: &[Argument],
+    context: &RuleContext,
+    variables: &LaunchVariables,
+) -> Vec<String> {
+    arguments
+        .iter()
+        .flat_map(|argument| argument.values(context))
+        .map(|value| variables.substitute(value))
+        .collect()
+}
+
+/// JVM arguments declared by the version. The legacy defaults are added whenever the
+/// declared ones don't set up the classpath themselves, which covers legacy versions and
+/// loader profiles that only add a few `arguments.jvm` entries on top of one.
+pub fn jvm_arguments(
+    version_json: &VersionJson,
+    context: &RuleContext,
+    variables: &LaunchVariables,
+) -> Vec<String> {
+    let declared = version_json
+        .arguments
+        .as_ref()
+        .and_then(|a| a.jvm.as_deref())
+        .unwrap_or_default();
+    let sets_classpath = declared
+        .iter()
+        .flat_map(|argument| argument.values(context))
+        .any(|value| value.contains("${classpath}"));
+
+    let mut arguments = Vec::new();
+    if !sets_classpath {
+        arguments.extend(LEGACY_JVM_ARGUMENTS.iter().map(|arg| variables.substitute(arg)));
+    }
+    arguments.extend(expand_arguments(declared, context, variables));
+    arguments
+}
+
+/// Game arguments declared by the version: `minecraftArguments` followed by `arguments.game`.
+/// Vanilla versions only use one of the two; loader profiles merged onto a legacy version
+/// can end up with both.
+pub fn game_arguments(
+    version_json: &VersionJson,
+    context: &RuleContext,
+    variables: &LaunchVariables,
+) -> Vec<String> {
+    let mut arguments: Vec<String> = version_json
+        .minecraft_arguments
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|arg| variables.substitute(arg))
+        .collect();
+
+    if let Some(game) = version_json.arguments.as_ref().and_then(|a| a.game.as_ref()) {
+        arguments.extend(expand_arguments(game, context, variables));
+    }
+    arguments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minecraft_api::parse_version_json;
+    use crate::testing::context;
+
+    /// 1.7.10, trimmed to the fields the launcher reads
+    const V1_7_10: &str = r#"{
+        "id": "1.7.10",
+        "type": "release",
+        "mainClass": "net.minecraft.client.main.Main",
+        "minecraftArguments": "--username ${auth_player_name} --version ${version_name} --gameDir ${game_directory} --assetsDir ${assets_root} --assetIndex ${assets_index_name} --uuid ${auth_uuid} --accessToken ${auth_access_token} --userProperties ${user_properties} --userType ${user_type}",
+        "assetIndex": {
+            "id": "1.7.10",
+            "sha1": "1863782e33ce7b584fc45b037325a1964e095d3e",
+            "size": 72996,
+            "totalSize": 112396854,
+            "url": "https://launchermeta.mojang.com/v1/packages/1863782e33ce7b584fc45b037325a1964e095d3e/1.7.10.json"
+        },
+        "assets": "1.7.10",
+        "minimumLauncherVersion": 13,
+        "libraries": [
+            {"name": "com.mojang:realms:1.3.5"},
+            {
+                "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.1",
+                "natives": {"linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows"},
+                "extract": {"exclude": ["META-INF/"]}
+            }
+        ]
+    }"#;
+
+    /// 1.12.2, trimmed to the fields the launcher reads
+    const V1_12_2: &str = r#"{
+        "id": "1.12.2",
+        "type": "release",
+        "mainClass": "net.minecraft.client.main.Main",
+        "minecraftArguments": "--username ${auth_player_name} --version ${version_name} --gameDir ${game_directory} --assetsDir ${assets_root} --assetIndex ${assets_index_name} --uuid ${auth_uuid} --accessToken ${auth_access_token} --userType ${user_type} --versionType ${version_type}",
+        "assetIndex": {
+            "id": "1.12",
+            "sha1": "1584b57c1a0b5e593fad1f5b8f78536ca640547b",
+            "size": 143138,
+            "totalSize": 129336389,
+            "url": "https://launchermeta.mojang.com/mc/assets/1.12/1584b57c1a0b5e593fad1f5b8f78536ca640547b/1.12.json"
+        },
+        "assets": "1.12",
+        "downloads": {
+            "client": {
+                "sha1": "0f275bc1547d01fa5f56ba34bdc87d981ee12daf",
+                "size": 10180113,
+                "url": "https://launcher.mojang.com/v1/objects/0f275bc1547d01fa5f56ba34bdc87d981ee12daf/client.jar"
+            }
+        },
+        "libraries": [
+            {
+                "name": "org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209",
+                "rules": [{"action": "allow"}, {"action": "disallow", "os": {"name": "osx"}}]
+            }
+        ]
+    }"#;
+
+    /// A pre-1.6 version JSON that only names its assets, without `assetIndex`
+    const V1_5_2: &str = r#"{
+        "id": "1.5.2",
+        "type": "old_release",
+        "mainClass": "net.minecraft.client.Minecraft",
+        "minecraftArguments": "${auth_player_name} ${auth_session} --gameDir ${game_directory} --assetsDir ${game_assets}",
+        "assets": "pre-1.6",
+        "libraries": []
+    }"#;
+
+    /// Loader profile shape: an empty `arguments.game` and a single JVM property
+    const LOADER_ON_1_12_2: &str = r#"{
+        "id": "fabric-loader-0.15.11-1.12.2",
+        "inheritsFrom": "1.12.2",
+        "type": "release",
+        "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+        "arguments": {
+            "game": [],
+            "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "]
+        },
+        "libraries": [{"name": "net.fabricmc:fabric-loader:0.15.11", "url": "https://maven.fabricmc.net/"}]
+    }"#;
+
+    fn variables() -> LaunchVariables {
+        LaunchVariables {
+            auth_player_name: "Steve".to_string(),
+            auth_uuid: "uuid".to_string(),
+            auth_access_token: "token".to_string(),
+            user_type: "legacy".to_string(),
+            version_name: "1.12.2".to_string(),
+            version_type: "release".to_string(),
+            game_directory: "/game".to_string(),
+            assets_root: "/game/assets".to_string(),
+            game_assets: "/game/resources".to_string(),
+            assets_index_name: "1.12".to_string(),
+            natives_directory: "/natives".to_string(),
+            classpath: "a.jar:b.jar".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn legacy_jvm_arguments() -> Vec<String> {
+        vec!["-Djava.library.path=/natives".to_string(), "-cp".to_string(), "a.jar:b.jar".to_string()]
+    }
+
+    #[test]
+    fn parses_1_7_10() {
+        let version = parse_version_json(V1_7_10).unwrap();
+
+        assert!(version.is_legacy());
+        assert!(version.java_version.is_none());
+        assert_eq!(version.asset_index_id(), "1.7.10");
+        assert_eq!(version.libraries.len(), 2);
+        assert_eq!(version.libraries[1].artifact_path(), None);
+
+        let game = game_arguments(&version, &context("linux", "x86_64"), &variables());
+        assert_eq!(&game[..2], ["--username", "Steve"]);
+        assert!(game.windows(2).any(|pair| pair == ["--userProperties", "{}"]));
+        assert_eq!(jvm_arguments(&version, &context("linux", "x86_64"), &variables()), legacy_jvm_arguments());
+    }
+
+    #[test]
+    fn parses_1_12_2() {
+        let version = parse_version_json(V1_12_2).unwrap();
+
+        assert!(version.is_legacy());
+        assert!(version.java_version.is_none());
+        assert_eq!(version.asset_index_id(), "1.12");
+        assert_eq!(version.downloads.as_ref().unwrap().client.size, Some(10180113));
+
+        let game = game_arguments(&version, &context("linux", "x86_64"), &variables());
+        assert_eq!(
+            game,
+            [
+                "--username", "Steve", "--version", "1.12.2", "--gameDir", "/game", "--assetsDir", "/game/assets",
+                "--assetIndex", "1.12", "--uuid", "uuid", "--accessToken", "token", "--userType", "legacy",
+                "--versionType", "release",
+            ]
+        );
+        assert_eq!(jvm_arguments(&version, &context("linux", "x86_64"), &variables()), legacy_jvm_arguments());
+    }
+
+    #[test]
+    fn parses_pre_1_6_without_asset_index() {
+        let version = parse_version_json(V1_5_2).unwrap();
+
+        assert!(version.is_legacy());
+        assert!(version.asset_index.is_none());
+        assert_eq!(version.asset_index_id(), "pre-1.6");
+
+        let game = game_arguments(&version, &context("linux", "x86_64"), &variables());
+        assert_eq!(game, ["Steve", "token", "--gameDir", "/game", "--assetsDir", "/game/resources"]);
+        assert_eq!(jvm_arguments(&version, &context("linux", "x86_64"), &variables()), legacy_jvm_arguments());
+    }
+
+    #[test]
+    fn loader_profile_on_legacy_parent() {
+        let parent = parse_version_json(V1_12_2).unwrap();
+        let version = parse_version_json(LOADER_ON_1_12_2).unwrap().merge_parent(parent);
+
+        assert!(version.is_legacy());
+        assert_eq!(version.asset_index_id(), "1.12");
+        assert_eq!(version.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(version.jar_id(), "1.12.2");
+
+        let game = game_arguments(&version, &context("linux", "x86_64"), &variables());
+        assert_eq!(&game[..2], ["--username", "Steve"]);
+        assert_eq!(game.len(), 18);
+
+        let mut expected_jvm = legacy_jvm_arguments();
+        expected_jvm.push("-DFabricMcEmu= net.minecraft.client.main.Main ".to_string());
+        assert_eq!(jvm_arguments(&version, &context("linux", "x86_64"), &variables()), expected_jvm);
+    }
+
+    #[test]
+    fn modern_jvm_arguments_are_not_doubled() {
+        let version = parse_version_json(
+            r#"{
+                "id": "1.20.4",
+                "mainClass": "net.minecraft.client.main.Main",
+                "arguments": {
+                    "game": ["--username", "${auth_player_name}"],
+                    "jvm": ["-Djava.library.path=${natives_directory}", "-cp", "${classpath}"]
+                },
+                "javaVersion": {"component": "java-runtime-gamma", "majorVersion": 17}
+            }"#,
+        )
+        .unwrap();
+
+        assert!(!version.is_legacy());
+        assert_eq!(game_arguments(&version, &context("linux", "x86_64"), &variables()), ["--username", "Steve"]);
+        assert_eq!(jvm_arguments(&version, &context("linux", "x86_64"), &variables()), legacy_jvm_arguments());
+    }
+}
@@ -0,0 +1,250 @@
+use crate::config::LauncherConfig;
+use crate::endpoints::EndpointRegistry;
+use crate::minecraft_api::{download_error, sha1_hex, LauncherError, Result};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Validators and age of a cached response, stored next to the body
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Unix time (seconds) the body was last confirmed fresh
+    fetched_at: u64,
+}
+
+/// On-disk cache for small metadata documents (version manifest, version JSONs).
+///
+/// Entries younger than the TTL are served without touching the network. Older
+/// ones are revalidated with `If-None-Match` / `If-Modified-Since`, and served
+/// stale when the server can't be reached.
+pub struct MetadataCache {
+    dir: PathBuf,
+    ttl: Duration,
+    endpoints: EndpointRegistry,
+}
+
+impl MetadataCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration, endpoints: EndpointRegistry) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+            endpoints,
+        }
+    }
+
+    pub fn from_config(config: &LauncherConfig) -> Self {
+        Self::new(
+            Self::default_dir(),
+            Duration::from_secs(config.metadata_cache_ttl),
+            EndpointRegistry::from_config(config),
+        )
+    }
+
+    fn default_dir() -> PathBuf {
+        if let Some(cache_dir) = dirs::cache_dir() {
+            cache_dir.join("satellite-launcher").join("metadata")
+        } else {
+            PathBuf::from("./cache/metadata")
+        }
+    }
+
+    /// Fetch `url` as text, going through the cache. Entries are keyed by the official URL,
+    /// so switching mirrors keeps the cache.
+    pub async fn fetch(&self, client: &Client, url: &str) -> Result<String> {
+        let cached = self.read(url);
+
+        if let Some((entry, body)) = &cached {
+            if now_secs().saturating_sub(entry.fetched_at) < self.ttl.as_secs() {
+                return Ok(body.clone());
+            }
+        }
+
+        let validators = cached.as_ref().map(|(entry, _)| entry);
+        let request = self.endpoints.send(client, url, |mut request| {
+            if let Some(etag) = validators.and_then(|entry| entry.etag.as_ref()) {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = validators.and_then(|entry| entry.last_modified.as_ref()) {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+            request
+        });
+
+        let response = match request.await {
+            Ok(response) => response,
+            // Offline or every mirror failing: a stale copy is better than nothing
+            Err(e) => {
+                return cached.map(|(_, body)| body).ok_or_else(|| download_error(url, e))
+            }
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            // Only possible with validators we didn't send; caching the empty body would be wrong
+            let Some((mut entry, body)) = cached else {
+                return Err(LauncherError::DownloadFailed {
+                    url: url.to_string(),
+                    reason: "304 Not Modified without a cached copy".to_string(),
+                });
+            };
+            entry.fetched_at = now_secs();
+            self.write_entry(&entry)?;
+            return Ok(body);
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            fetched_at: now_secs(),
+        };
+
+        let body = response.text().await?;
+        self.write(&entry, &body)?;
+        Ok(body)
+    }
+
+    /// Forget the cached copy of `url`
+    pub fn invalidate(&self, url: &str) {
+        let _ = fs::remove_file(self.entry_path(url));
+        let _ = fs::remove_file(self.body_path(url));
+    }
+
+    fn key(url: &str) -> String {
+        sha1_hex(url.as_bytes())
+    }
+
+    fn body_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.body", Self::key(url)))
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", Self::key(url)))
+    }
+
+    fn read(&self, url: &str) -> Option<(CacheEntry, String)> {
+        let entry: CacheEntry =
+            serde_json::from_str(&fs::read_to_string(self.entry_path(url)).ok()?).ok()?;
+        let body = fs::read_to_string(self.body_path(url)).ok()?;
+        Some((entry, body))
+    }
+
+    fn write(&self, entry: &CacheEntry, body: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.body_path(&entry.url), body)?;
+        self.write_entry(entry)
+    }
+
+    fn write_entry(&self, entry: &CacheEntry) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(&entry.url), serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{response, StubServer};
+
+    const LAST_MODIFIED_VALUE: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+    fn cache(dir: &tempfile::TempDir) -> MetadataCache {
+        MetadataCache::new(dir.path(), Duration::from_secs(600), EndpointRegistry::default())
+    }
+
+    fn seed(cache: &MetadataCache, url: &str, body: &str, fetched_at: u64) {
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag: Some("\"v1\"".to_string()),
+            last_modified: Some(LAST_MODIFIED_VALUE.to_string()),
+            fetched_at,
+        };
+        cache.write(&entry, body).unwrap();
+    }
+
+    #[tokio::test]
+    async fn fresh_entry_skips_the_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(&dir);
+        let server = StubServer::start(vec![response("200 OK", &[], b"new")]).await;
+        seed(&cache, &server.url, "cached", now_secs());
+
+        assert_eq!(cache.fetch(&Client::new(), &server.url).await.unwrap(), "cached");
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn not_modified_revalidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(&dir);
+        let server = StubServer::start(vec![response("304 Not Modified", &[], b"")]).await;
+        seed(&cache, &server.url, "cached", 0);
+
+        assert_eq!(cache.fetch(&Client::new(), &server.url).await.unwrap(), "cached");
+
+        let requests = server.requests();
+        assert!(requests[0].contains("if-none-match: \"v1\""));
+        assert!(requests[0].contains(&format!("if-modified-since: {}", LAST_MODIFIED_VALUE.to_lowercase())));
+        let (entry, body) = cache.read(&server.url).unwrap();
+        assert_eq!(body, "cached");
+        assert!(entry.fetched_at + 60 > now_secs());
+    }
+
+    #[tokio::test]
+    async fn changed_body_replaces_cached_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(&dir);
+        let server = StubServer::start(vec![response("200 OK", &[("ETag", "\"v2\"")], b"new")]).await;
+        seed(&cache, &server.url, "cached", 0);
+
+        assert_eq!(cache.fetch(&Client::new(), &server.url).await.unwrap(), "new");
+
+        let (entry, body) = cache.read(&server.url).unwrap();
+        assert_eq!(body, "new");
+        assert_eq!(entry.etag.as_deref(), Some("\"v2\""));
+        assert_eq!(entry.last_modified, None);
+    }
+
+    #[tokio::test]
+    async fn stale_copy_served_when_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(&dir);
+        // Bind and drop a listener to get a port nothing listens on
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+        drop(listener);
+        seed(&cache, &url, "cached", 0);
+
+        assert_eq!(cache.fetch(&Client::new(), &url).await.unwrap(), "cached");
+    }
+
+    #[tokio::test]
+    async fn not_modified_without_cached_copy_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(&dir);
+        let server = StubServer::start(vec![response("304 Not Modified", &[], b"")]).await;
+
+        assert!(cache.fetch(&Client::new(), &server.url).await.is_err());
+        assert!(cache.read(&server.url).is_none());
+    }
+}
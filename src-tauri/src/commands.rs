@@ -1,5 +1,7 @@
-use crate::config::LauncherConfig;
-use crate::launcher::MinecraftLauncher;
+use crate::config::{ConfigIssue, LauncherConfig};
+use crate::java::JavaInstallation;
+use crate::launcher::{MinecraftLauncher, VerificationReport};
+use crate::store::StoreGcReport;
 use crate::minecraft_api::MinecraftVersion;
 use crate::mods::{ModManager, ModInfo, ModSearchResult, ModLoader, search_mods};
 use tauri::Emitter;
@@ -7,16 +9,19 @@ use tauri::Emitter;
 /// Get available Minecraft versions
 #[tauri::command]
 pub async fn get_versions() -> Result<Vec<MinecraftVersion>, String> {
-    let launcher = MinecraftLauncher::new();
+    let launcher = MinecraftLauncher::new().map_err(|e| e.to_string())?;
     launcher.get_versions().await.map_err(|e| e.to_string())
 }
 
 /// Start the Minecraft launcher
 #[tauri::command]
 pub async fn start_launcher(window: tauri::Window, version: String) -> Result<(), String> {
-    let launcher = MinecraftLauncher::new();
-    
-    match launcher.launch_version(window.clone(), version).await {
+    let launched = match MinecraftLauncher::new() {
+        Ok(launcher) => launcher.launch_version(window.clone(), version).await,
+        Err(e) => Err(e),
+    };
+
+    match launched {
         Ok(()) => Ok(()),
         Err(e) => {
             let error_msg = format!("Launch failed: {}", e);
@@ -26,6 +31,34 @@ pub async fn start_launcher(window: tauri::Window, version: String) -> Result<()
     }
 }
 
+/// Verify a version's game files and re-download any that are missing or corrupted
+#[tauri::command]
+pub async fn verify_game_files(window: tauri::Window, version: String) -> Result<VerificationReport, String> {
+    let verified = match MinecraftLauncher::new() {
+        Ok(launcher) => launcher.verify_game_files(window.clone(), version).await,
+        Err(e) => Err(e),
+    };
+
+    match verified {
+        Ok(report) => Ok(report),
+        Err(e) => {
+            let error_msg = format!("Verification failed: {}", e);
+            window.emit("error", &error_msg).ok();
+            Err(error_msg)
+        }
+    }
+}
+
+/// Remove shared store objects no installed version references. The configured game
+/// directory is always scanned; other instances sharing the store can be listed too.
+#[tauri::command]
+pub async fn gc_shared_store(game_directories: Option<Vec<String>>) -> Result<StoreGcReport, String> {
+    let launcher = MinecraftLauncher::new().map_err(|e| e.to_string())?;
+    launcher
+        .collect_store_garbage(&game_directories.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
 /// Get launcher configuration
 #[tauri::command]
 pub async fn get_config() -> Result<LauncherConfig, String> {
@@ -38,25 +71,45 @@ pub async fn update_config(config: LauncherConfig) -> Result<(), String> {
     config.save().map_err(|e| e.to_string())
 }
 
-/// Validate configuration before saving
+/// Validate configuration before saving; every problem is reported with the field it belongs to
 #[tauri::command]
-pub async fn validate_config(config: LauncherConfig) -> Result<bool, String> {
-    // Basic validation logic
+pub async fn validate_config(config: LauncherConfig) -> Result<Vec<ConfigIssue>, String> {
+    let mut issues = Vec::new();
+
     if config.player_name.trim().is_empty() {
-        return Err("Player name cannot be empty".to_string());
+        issues.push(ConfigIssue::error("player_name", "Player name cannot be empty"));
     }
-    
+
     if config.player_name.len() > 16 {
-        return Err("Player name cannot be longer than 16 characters".to_string());
+        issues.push(ConfigIssue::error("player_name", "Player name cannot be longer than 16 characters"));
     }
-    
+
     if !config.game_directory.trim().is_empty() {
         if !std::path::Path::new(&config.game_directory).exists() {
-            return Err("Game directory does not exist".to_string());
+            // Created on first launch, so not worth blocking the save over
+            issues.push(ConfigIssue::warning("game_directory", "Game directory does not exist yet"));
         }
     }
-    
-    Ok(true)
+
+    // Runs the configured Java executables
+    let checked = tokio::task::spawn_blocking(move || config.check())
+        .await
+        .map_err(|e| e.to_string())?;
+    for issue in checked {
+        if !issues.iter().any(|known: &ConfigIssue| known.field == issue.field && known.message == issue.message) {
+            issues.push(issue);
+        }
+    }
+
+    Ok(issues)
+}
+
+/// List the Java runtimes installed on this machine, for the settings picker
+#[tauri::command]
+pub async fn get_java_installations() -> Result<Vec<JavaInstallation>, String> {
+    tokio::task::spawn_blocking(crate::java::discover_java)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// Get system information for diagnostics
@@ -1,12 +1,20 @@
+use crate::endpoints::Endpoint;
+use crate::java::{self, JavaSource};
 use crate::minecraft_api::{LauncherError, Result};
+use md5::{Digest, Md5};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use sysinfo::{MemoryRefreshKind, RefreshKind, System};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LauncherConfig {
     pub game_directory: String,
     pub java_executable: Option<String>,
+    /// Java executable to use for versions requiring a given major Java version
+    pub java_executables: HashMap<u32, String>,
 
     pub min_memory: u32,
     pub max_memory: u32,
@@ -14,22 +22,94 @@ pub struct LauncherConfig {
     pub jvm_args: Vec<String>,
 
     pub player_name: String,
+    /// Pinned UUID; when unset the offline UUID is derived from `player_name`
     pub player_uuid: Option<String>,
 
     pub download_timeout: u64,
     pub max_retries: u32,
     pub concurrent_downloads: u32,
+    /// Seconds a cached version manifest / version JSON is used before revalidating it
+    pub metadata_cache_ttl: u64,
+    /// Base URLs to use instead of an endpoint's official host, tried in order
+    pub mirrors: HashMap<Endpoint, Vec<String>>,
+    pub proxy: Option<ProxyConfig>,
+    /// PEM file with extra root certificates, e.g. for a TLS-intercepting proxy
+    pub ca_bundle: Option<String>,
+    /// Share libraries, client jars and assets between game directories through one
+    /// content-addressed store
+    pub use_shared_store: bool,
+    /// Never touch the network: launch from installed files only
+    pub offline_mode: bool,
+    /// Location of the shared store; defaults to the launcher data directory
+    pub shared_store_dir: Option<String>,
 
     pub theme: String,
     pub show_snapshots: bool,
     pub show_beta_versions: bool,
 }
 
+/// Largest heap a 32-bit JVM can reliably reserve. The address space is 4GB, but the OS
+/// and the JVM itself leave well under 2GB of it contiguous, especially on Windows.
+const MAX_32BIT_HEAP_MB: u32 = 1536;
+
+/// A problem with one configuration field, for the settings screen to show next to it
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    /// Config field name, e.g. `max_memory` or `java_executables.17`
+    pub field: String,
+    pub message: String,
+    /// Warnings are worth showing but don't stop the launcher from using the config
+    pub warning: bool,
+}
+
+impl ConfigIssue {
+    pub fn error(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+            warning: false,
+        }
+    }
+
+    pub fn warning(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+            warning: true,
+        }
+    }
+}
+
+impl From<ConfigIssue> for LauncherError {
+    fn from(issue: ConfigIssue) -> Self {
+        LauncherError::ConfigValidation {
+            field: issue.field,
+            message: issue.message,
+        }
+    }
+}
+
+/// Installed RAM in MB, or 0 when it can't be read
+fn physical_memory_mb() -> u64 {
+    let system =
+        System::new_with_specifics(RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram()));
+    system.total_memory() / (1024 * 1024)
+}
+
+/// Proxy for every request; `url` may use `http`, `https`, `socks5` or `socks5h`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
 impl Default for LauncherConfig {
     fn default() -> Self {
         Self {
             game_directory: "./.minecraft_fake".to_string(),
             java_executable: None,
+            java_executables: HashMap::new(),
             min_memory: 1024,
             max_memory: 4096,
             jvm_args: vec![],
@@ -38,6 +118,13 @@ impl Default for LauncherConfig {
             download_timeout: 30,
             max_retries: 3,
             concurrent_downloads: 8,
+            metadata_cache_ttl: 600,
+            mirrors: HashMap::new(),
+            proxy: None,
+            ca_bundle: None,
+            use_shared_store: true,
+            offline_mode: false,
+            shared_store_dir: None,
             theme: "auto".to_string(),
             show_snapshots: false,
             show_beta_versions: false,
@@ -80,36 +167,103 @@ impl LauncherConfig {
         }
     }
 
+    /// Fail with the first error [`Self::check`] finds; warnings are ignored
     pub fn validate(&self) -> Result<()> {
+        match self.check().into_iter().find(|issue| !issue.warning) {
+            Some(issue) => Err(issue.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Every problem with the configuration, each tied to the field it concerns.
+    ///
+    /// Configured Java executables are run to read their version and bitness, so this
+    /// takes a moment per executable.
+    pub fn check(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+
         if self.min_memory > self.max_memory {
-            return Err(LauncherError::ConfigValidation {
-                field: "memory".to_string(),
-                message: "Min memory cannot be greater than max memory".to_string(),
-            });
+            issues.push(ConfigIssue::error("memory", "Min memory cannot be greater than max memory"));
         }
 
         if self.max_memory < 512 {
-            return Err(LauncherError::ConfigValidation {
-                field: "max_memory".to_string(),
-                message: "Max memory must be at least 512MB".to_string(),
-            });
+            issues.push(ConfigIssue::error("max_memory", "Max memory must be at least 512MB"));
         }
 
         if self.game_directory.is_empty() {
-            return Err(LauncherError::ConfigValidation {
-                field: "game_directory".to_string(),
-                message: "Game directory cannot be empty".to_string(),
-            });
+            issues.push(ConfigIssue::error("game_directory", "Game directory cannot be empty"));
         }
 
         if self.player_name.is_empty() {
-            return Err(LauncherError::ConfigValidation {
-                field: "player_name".to_string(),
-                message: "Player name cannot be empty".to_string(),
-            });
+            issues.push(ConfigIssue::error("player_name", "Player name cannot be empty"));
         }
 
-        Ok(())
+        if let Some(uuid) = self.player_uuid.as_deref().map(str::trim).filter(|uuid| !uuid.is_empty()) {
+            if !is_valid_uuid(uuid) {
+                issues.push(ConfigIssue::error("player_uuid", format!("{} is not a valid UUID", uuid)));
+            }
+        }
+
+        // Surfaces bad proxy URLs and unreadable CA bundles
+        match crate::http::build_client(self) {
+            Ok(_) => {}
+            Err(LauncherError::ConfigValidation { field, message }) => issues.push(ConfigIssue::error(&field, message)),
+            Err(e) => issues.push(ConfigIssue::error("proxy", e.to_string())),
+        }
+
+        self.check_java(&mut issues);
+        issues
+    }
+
+    /// Run the configured Java executables and check their version and bitness against
+    /// the per-version overrides and the memory settings
+    fn check_java(&self, issues: &mut Vec<ConfigIssue>) {
+        let mut executables: Vec<(String, &String, Option<u32>)> = Vec::new();
+        if let Some(executable) = &self.java_executable {
+            executables.push(("java_executable".to_string(), executable, None));
+        }
+        let mut overrides: Vec<_> = self.java_executables.iter().collect();
+        overrides.sort();
+        for (major, executable) in overrides {
+            executables.push((format!("java_executables.{}", major), executable, Some(*major)));
+        }
+
+        for (field, executable, required) in executables {
+            let Some(java) = java::probe(Path::new(executable), JavaSource::Config) else {
+                issues.push(ConfigIssue::error(
+                    &field,
+                    format!("{} could not be run or did not report a Java version", executable),
+                ));
+                continue;
+            };
+
+            if let Some(required) = required {
+                if !java::is_compatible(required, java.major_version) {
+                    issues.push(ConfigIssue::error(
+                        &field,
+                        format!("{} is Java {}, which cannot run versions requiring Java {}", executable, java.major_version, required),
+                    ));
+                }
+            }
+
+            if java.bits == Some(32) && self.max_memory > MAX_32BIT_HEAP_MB {
+                issues.push(ConfigIssue::warning(
+                    "max_memory",
+                    format!(
+                        "{} is a 32-bit Java, which cannot use more than {}MB; lower max memory or use a 64-bit Java",
+                        executable, MAX_32BIT_HEAP_MB
+                    ),
+                ));
+            }
+        }
+
+        let total_memory_mb = physical_memory_mb();
+        if total_memory_mb > 0 && u64::from(self.max_memory) > total_memory_mb {
+            issues.push(ConfigIssue::warning(
+                "max_memory",
+                format!("Max memory exceeds the {}MB of RAM installed", total_memory_mb),
+            ));
+        }
     }
 
     pub fn get_jvm_args(&self) -> Vec<String> {
@@ -128,4 +282,108 @@ impl LauncherConfig {
             .clone()
             .unwrap_or_else(|| "java".to_string())
     }
+
+    /// UUID the game is launched with: the pinned `player_uuid` if set, otherwise the
+    /// offline UUID vanilla servers derive from the player name
+    pub fn get_player_uuid(&self) -> String {
+        match self.player_uuid.as_deref().map(str::trim) {
+            Some(uuid) if !uuid.is_empty() => uuid.to_string(),
+            _ => offline_uuid(&self.player_name),
+        }
+    }
+}
+
+/// Name-based (version 3) UUID of `OfflinePlayer:<name>`, matching Java's
+/// `UUID.nameUUIDFromBytes` as used by servers in offline mode
+pub fn offline_uuid(name: &str) -> String {
+    let mut hash: [u8; 16] = Md5::digest(format!("OfflinePlayer:{}", name).as_bytes()).into();
+    hash[6] = (hash[6] & 0x0f) | 0x30;
+    hash[8] = (hash[8] & 0x3f) | 0x80;
+
+    let hex: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+/// 32 hex digits, with or without the usual hyphens
+fn is_valid_uuid(uuid: &str) -> bool {
+    let digits: Vec<char> = uuid.chars().filter(|c| *c != '-').collect();
+    let hyphens = uuid.len() - digits.len();
+    digits.len() == 32
+        && digits.iter().all(char::is_ascii_hexdigit)
+        && (hyphens == 0 || (hyphens == 4 && uuid.split('-').map(str::len).eq([8, 4, 4, 4, 12])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shell script answering `-XshowSettings:properties -version` like a 64-bit JDK `version`
+    #[cfg(unix)]
+    fn fake_java(dir: &Path, version: &str) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(format!("java-{}", version));
+        let script = format!(
+            "#!/bin/sh\n\
+             echo 'Property settings:' >&2\n\
+             echo '    java.version = {version}' >&2\n\
+             echo '    sun.arch.data.model = 64' >&2\n\
+             echo 'openjdk version \"{version}\"' >&2\n"
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn java_overrides_accept_compatible_newer_releases() {
+        let dir = tempfile::tempdir().unwrap();
+        let jdk21 = fake_java(dir.path(), "21.0.2");
+        let config = LauncherConfig {
+            java_executables: HashMap::from([(17, jdk21.clone()), (8, jdk21)]),
+            ..Default::default()
+        };
+
+        let issues = config.check();
+        assert!(!issues.iter().any(|issue| issue.field == "java_executables.17"));
+        assert!(issues.iter().any(|issue| issue.field == "java_executables.8" && !issue.warning));
+    }
+
+    #[test]
+    fn offline_uuid_matches_java() {
+        // UUID.nameUUIDFromBytes("OfflinePlayer:Notch".getBytes(UTF_8))
+        assert_eq!(offline_uuid("Notch"), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+    }
+
+    #[test]
+    fn player_uuid_prefers_a_pinned_value() {
+        let pinned = LauncherConfig {
+            player_name: "Notch".to_string(),
+            player_uuid: Some(" 069a79f4-44e9-4726-a5be-fca90e38aaf5 ".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pinned.get_player_uuid(), "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+
+        for unset in [None, Some(String::new()), Some("  \t".to_string())] {
+            let config = LauncherConfig {
+                player_name: "Notch".to_string(),
+                player_uuid: unset,
+                ..Default::default()
+            };
+            assert_eq!(config.get_player_uuid(), offline_uuid("Notch"));
+        }
+    }
+
+    #[test]
+    fn uuid_layout() {
+        assert!(is_valid_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf5"));
+        assert!(is_valid_uuid("069a79f444e94726a5befca90e38aaf5"));
+        assert!(is_valid_uuid("069A79F4-44E9-4726-A5BE-FCA90E38AAF5"));
+        // Right digits, hyphens in the wrong places
+        assert!(!is_valid_uuid("069a79f444e9-4726-a5be-fca9-0e38aaf5"));
+        assert!(!is_valid_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf"));
+        assert!(!is_valid_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf5-"));
+        assert!(!is_valid_uuid("g69a79f4-44e9-4726-a5be-fca90e38aaf5"));
+    }
 }
@@ -1,136 +1,266 @@
+use crate::config::LauncherConfig;
+use crate::endpoints::EndpointRegistry;
+use crate::http;
+use crate::store::SharedStore;
 use crate::errors::{LauncherError, LauncherResult};
+use crate::minecraft_api;
+use crate::progress::ProgressTracker;
 use reqwest::Client;
-use sha1::{Digest, Sha1};
-use std::fs::{self, File};
-use std::io::Read;
+use std::fs;
 use std::path::Path;
+use std::time::Duration;
 use tauri::Emitter;
 
+/// Concurrency, retry and timeout settings shared by every download
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadLimits {
+    pub concurrent_downloads: usize,
+    pub max_retries: u32,
+    /// Applies to connecting and to each read, so large files aren't cut off mid-transfer
+    pub timeout: Duration,
+}
+
+impl DownloadLimits {
+    pub fn from_config(config: &LauncherConfig) -> Self {
+        Self {
+            concurrent_downloads: config.concurrent_downloads.max(1) as usize,
+            max_retries: config.max_retries,
+            timeout: Duration::from_secs(config.download_timeout.max(1)),
+        }
+    }
+}
+
 pub struct Downloader {
     client: Client,
+    limits: DownloadLimits,
+    progress: ProgressTracker,
+    endpoints: EndpointRegistry,
+    store: Option<SharedStore>,
 }
 
 impl Downloader {
-    pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-        }
+    /// Fails with `ConfigValidation` when the proxy or CA bundle settings are unusable
+    pub fn from_config(config: &LauncherConfig) -> LauncherResult<Self> {
+        Ok(Self {
+            client: http::build_client(config)?,
+            limits: DownloadLimits::from_config(config),
+            progress: ProgressTracker::new(),
+            endpoints: EndpointRegistry::from_config(config),
+            store: SharedStore::from_config(config),
+        })
+    }
+
+    pub fn limits(&self) -> &DownloadLimits {
+        &self.limits
+    }
+
+    /// Byte-level progress of every download made through this downloader
+    pub fn progress(&self) -> &ProgressTracker {
+        &self.progress
     }
 
     pub fn client(&self) -> &Client {
         &self.client
     }
 
+    /// Mirror configuration every download URL is rewritten through
+    pub fn endpoints(&self) -> &EndpointRegistry {
+        &self.endpoints
+    }
+
+    /// Content-addressed store shared between game directories, if enabled
+    pub fn store(&self) -> Option<&SharedStore> {
+        self.store.as_ref()
+    }
+
+    /// GET `url` through the configured mirrors
+    pub async fn get(&self, url: &str) -> reqwest::Result<reqwest::Response> {
+        self.endpoints.get(&self.client, url).await
+    }
+
     /// Download a file with progress reporting
     pub async fn download_file_with_progress(
         &self,
         url: &str,
-        path: &str,
+        path: &Path,
         window: Option<&tauri::Window>,
     ) -> LauncherResult<()> {
-        if let Some(parent) = Path::new(path).parent() {
-            fs::create_dir_all(parent).map_err(|e| LauncherError::Io(e))?;
-        }
-
-        let response = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| LauncherError::DownloadFailed {
-                url: url.to_string(),
-                reason: e.to_string(),
-            })?;
-
-        if !response.status().is_success() {
-            return Err(LauncherError::DownloadFailed {
-                url: url.to_string(),
-                reason: format!("HTTP {}", response.status()),
-            });
-        }
-
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| LauncherError::DownloadFailed {
-                url: url.to_string(),
-                reason: e.to_string(),
-            })?;
+        self.download_checked(url, path, None, None, window).await
+    }
 
-        fs::write(path, bytes).map_err(|e| LauncherError::Io(e))?;
+    /// Stream a file to disk, hashing it on the fly; `path` is only replaced once the
+    /// expected SHA-1 and size (when given) match. Mirrors for `url` are tried in order.
+    pub async fn download_checked(
+        &self,
+        url: &str,
+        path: &Path,
+        expected_sha1: Option<&str>,
+        expected_size: Option<u64>,
+        window: Option<&tauri::Window>,
+    ) -> LauncherResult<()> {
+        let mut last_error = None;
+        for candidate in self.endpoints.candidates(url) {
+            self.progress.start_file(path, expected_size);
+            let result =
+                minecraft_api::download_resumable(&self.client, &candidate, path, expected_sha1, expected_size, |n| {
+                    self.progress.advance(path, n)
+                })
+                .await;
 
-        if let Some(window) = window {
-            window
-                .emit("log", format!("Downloaded: {}", path))
-                .ok();
+            match result {
+                Ok(()) => {
+                    self.progress.finish_file(path);
+                    if let Some(window) = window {
+                        window
+                            .emit("log", format!("Downloaded: {}", path.display()))
+                            .ok();
+                    }
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.progress.fail_file(path);
+                    last_error = Some(match e {
+                        minecraft_api::LauncherError::Network(e) => minecraft_api::download_error(&candidate, e).into(),
+                        other => other.into(),
+                    });
+                }
+            }
         }
 
-        Ok(())
+        Err(last_error.expect("candidates() always returns at least one URL"))
     }
 
-    /// Verify file hash and re-download if necessary
+    /// Verify file hash and re-download if necessary.
+    ///
+    /// Files with a known SHA-1 are linked from the shared store when it has them, and
+    /// added to it otherwise. Downloaded files are checked against `expected_sha1` /
+    /// `expected_size` and retried up to `max_retries` times before failing with `HashMismatch`.
     pub async fn verify_and_download(
         &self,
         url: &str,
-        path: &str,
+        path: &Path,
         expected_sha1: Option<&str>,
+        expected_size: Option<u64>,
         window: Option<&tauri::Window>,
     ) -> LauncherResult<bool> {
-        let mut needs_download = true;
-
         // Check if file exists and verify hash if provided
-        if Path::new(path).exists() {
-            if let Some(expected_hash) = expected_sha1 {
-                match self.calculate_file_hash(path) {
-                    Ok(actual_hash) => {
-                        if actual_hash == expected_hash {
-                            needs_download = false;
-                            if let Some(window) = window {
-                                window
-                                    .emit("log", format!("File OK: {}", path))
-                                    .ok();
-                            }
-                        } else {
-                            if let Some(window) = window {
-                                window
-                                    .emit("log", format!("Hash mismatch, re-downloading: {}", path))
-                                    .ok();
-                            }
-                        }
+        if path.exists() {
+            match self.verify_file_blocking(path, expected_sha1, expected_size).await {
+                Ok(()) => {
+                    self.progress.skip(path, expected_size);
+                    self.share(path, expected_sha1);
+                    if let Some(window) = window {
+                        window
+                            .emit("log", format!("File OK: {}", path.display()))
+                            .ok();
                     }
-                    Err(_) => {
-                        // If we can't calculate hash, re-download
-                        needs_download = true;
+                    return Ok(false);
+                }
+                Err(_) => {
+                    if let Some(window) = window {
+                        window
+                            .emit("log", format!("Hash mismatch, re-downloading: {}", path.display()))
+                            .ok();
                     }
                 }
-            } else {
-                // No hash provided, assume file is OK if it exists
-                needs_download = false;
             }
         }
 
-        if needs_download {
-            self.download_file_with_progress(url, path, window).await?;
+        if self.link_from_store(path, expected_sha1, expected_size).await {
+            self.progress.skip(path, expected_size);
+            if let Some(window) = window {
+                window
+                    .emit("log", format!("Linked from shared store: {}", path.display()))
+                    .ok();
+            }
+            return Ok(false);
         }
 
-        Ok(needs_download)
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = self
+                .download_checked(url, path, expected_sha1, expected_size, window)
+                .await;
+
+            match result {
+                Ok(()) => {
+                    self.share(path, expected_sha1);
+                    return Ok(true);
+                }
+                Err(e) if attempt > self.limits.max_retries => {
+                    // Don't leave a corrupt file behind for the next "exists" check
+                    if matches!(e, LauncherError::HashMismatch { .. }) {
+                        fs::remove_file(path).ok();
+                    }
+                    return Err(e);
+                }
+                Err(e) => {
+                    if let Some(window) = window {
+                        window
+                            .emit("log", format!("Retrying {} ({}/{}): {}", path.display(), attempt, self.limits.max_retries, e))
+                            .ok();
+                    }
+                }
+            }
+        }
     }
 
-    /// Calculate SHA1 hash of a file
-    pub fn calculate_file_hash(&self, path: &str) -> LauncherResult<String> {
-        let mut file = File::open(path).map_err(|e| LauncherError::Io(e))?;
-        let mut hasher = Sha1::new();
-        let mut buffer = [0; 8192];
+    /// Link `path` to the shared store's copy of `sha1`, if there is one that verifies.
+    /// A stored object that fails verification is dropped from the store.
+    pub async fn link_from_store(&self, path: &Path, sha1: Option<&str>, size: Option<u64>) -> bool {
+        let (Some(store), Some(sha1)) = (&self.store, sha1) else {
+            return false;
+        };
+        if !store.contains(sha1, size) || store.link_into(sha1, path).is_err() {
+            return false;
+        }
+        if self.verify_file_blocking(path, Some(sha1), size).await.is_ok() {
+            return true;
+        }
+        store.remove(sha1);
+        fs::remove_file(path).ok();
+        false
+    }
 
-        loop {
-            let n = file.read(&mut buffer).map_err(|e| LauncherError::Io(e))?;
-            if n == 0 {
-                break;
-            }
-            hasher.update(&buffer[..n]);
+    /// Add a verified file to the shared store; sharing is best effort. Only pass files
+    /// whose SHA-1 has just been checked, since other instances will link to them.
+    pub fn share(&self, path: &Path, sha1: Option<&str>) {
+        if let (Some(store), Some(sha1)) = (&self.store, sha1) {
+            store.insert(sha1, path).ok();
         }
+    }
+
+    /// Check a file on disk against the expected size and SHA-1; missing expectations always pass
+    pub fn verify_file(
+        &self,
+        path: &Path,
+        expected_sha1: Option<&str>,
+        expected_size: Option<u64>,
+    ) -> LauncherResult<()> {
+        check_file(path, expected_sha1, expected_size)
+    }
+
+    /// [`Self::verify_file`] on the blocking thread pool, for callers on the async runtime
+    pub async fn verify_file_blocking(
+        &self,
+        path: &Path,
+        expected_sha1: Option<&str>,
+        expected_size: Option<u64>,
+    ) -> LauncherResult<()> {
+        let (owned_path, expected_sha1) = (path.to_path_buf(), expected_sha1.map(str::to_string));
+        tokio::task::spawn_blocking(move || check_file(&owned_path, expected_sha1.as_deref(), expected_size))
+            .await
+            .map_err(|e| LauncherError::FileSystemError {
+                operation: "verify_file".to_string(),
+                path: path.to_string_lossy().to_string(),
+                error: e.to_string(),
+            })?
+    }
 
-        Ok(format!("{:x}", hasher.finalize()))
+    /// Calculate SHA1 hash of a file
+    pub fn calculate_file_hash(&self, path: &Path) -> LauncherResult<String> {
+        Ok(minecraft_api::sha1_file(path)?)
     }
 
     /// Download multiple files in parallel
@@ -164,3 +294,29 @@ impl Downloader {
         Ok(())
     }
 }
+
+fn check_file(path: &Path, expected_sha1: Option<&str>, expected_size: Option<u64>) -> LauncherResult<()> {
+    if let Some(expected_size) = expected_size {
+        let actual_size = fs::metadata(path)?.len();
+        if actual_size != expected_size {
+            return Err(LauncherError::HashMismatch {
+                file: path.to_string_lossy().to_string(),
+                expected: format!("{} bytes", expected_size),
+                actual: format!("{} bytes", actual_size),
+            });
+        }
+    }
+
+    if let Some(expected_hash) = expected_sha1 {
+        let actual_hash = minecraft_api::sha1_file(path)?;
+        if !actual_hash.eq_ignore_ascii_case(expected_hash) {
+            return Err(LauncherError::HashMismatch {
+                file: path.to_string_lossy().to_string(),
+                expected: expected_hash.to_string(),
+                actual: actual_hash,
+            });
+        }
+    }
+
+    Ok(())
+}
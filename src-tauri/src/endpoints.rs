@@ -0,0 +1,190 @@
+use crate::config::LauncherConfig;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Upstream services the launcher downloads from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    /// Version manifest and version JSONs
+    MojangMeta,
+    /// Client jars
+    MojangData,
+    MojangLibraries,
+    /// Asset objects
+    MojangResources,
+    FabricMeta,
+    FabricMaven,
+    Modrinth,
+}
+
+impl Endpoint {
+    pub const ALL: [Endpoint; 7] = [
+        Endpoint::MojangMeta,
+        Endpoint::MojangData,
+        Endpoint::MojangLibraries,
+        Endpoint::MojangResources,
+        Endpoint::FabricMeta,
+        Endpoint::FabricMaven,
+        Endpoint::Modrinth,
+    ];
+
+    /// Official base URLs, the first one being canonical. URLs under any of them belong to this endpoint.
+    pub fn official_bases(self) -> &'static [&'static str] {
+        match self {
+            Endpoint::MojangMeta => &["https://piston-meta.mojang.com", "https://launchermeta.mojang.com"],
+            Endpoint::MojangData => &["https://piston-data.mojang.com", "https://launcher.mojang.com"],
+            Endpoint::MojangLibraries => &["https://libraries.minecraft.net"],
+            Endpoint::MojangResources => &["https://resources.download.minecraft.net"],
+            Endpoint::FabricMeta => &["https://meta.fabricmc.net"],
+            Endpoint::FabricMaven => &["https://maven.fabricmc.net"],
+            Endpoint::Modrinth => &["https://api.modrinth.com"],
+        }
+    }
+
+    /// Official URL of `path` on this endpoint
+    pub fn url(self, path: &str) -> String {
+        format!("{}/{}", self.official_bases()[0], path.trim_start_matches('/'))
+    }
+
+    /// Endpoint `url` belongs to, and the part of the URL after its base
+    fn split(url: &str) -> Option<(Endpoint, &str)> {
+        Self::ALL.into_iter().find_map(|endpoint| {
+            endpoint.official_bases().iter().find_map(|base| {
+                let rest = url.strip_prefix(base)?;
+                (rest.is_empty() || rest.starts_with('/')).then_some((endpoint, rest))
+            })
+        })
+    }
+}
+
+/// Rewrites official URLs onto configured mirrors.
+///
+/// Each endpoint maps to an ordered list of base URLs that are tried in turn. Endpoints
+/// without an entry only use the official host; to fall back to it after a mirror, list
+/// it explicitly.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointRegistry {
+    mirrors: HashMap<Endpoint, Vec<String>>,
+}
+
+impl EndpointRegistry {
+    pub fn new(mirrors: HashMap<Endpoint, Vec<String>>) -> Self {
+        Self { mirrors }
+    }
+
+    pub fn from_config(config: &LauncherConfig) -> Self {
+        Self::new(config.mirrors.clone())
+    }
+
+    /// URLs to try for `url`, in order. URLs outside every known endpoint are used as is.
+    pub fn candidates(&self, url: &str) -> Vec<String> {
+        let Some((endpoint, rest)) = Endpoint::split(url) else {
+            return vec![url.to_string()];
+        };
+
+        match self.mirrors.get(&endpoint) {
+            Some(bases) if !bases.is_empty() => bases
+                .iter()
+                .map(|base| format!("{}{}", base.trim_end_matches('/'), rest))
+                .collect(),
+            _ => vec![url.to_string()],
+        }
+    }
+
+    /// GET `url`, falling back through its mirrors on connection errors and error statuses
+    pub async fn get(&self, client: &Client, url: &str) -> reqwest::Result<Response> {
+        self.send(client, url, |request| request).await
+    }
+
+    /// Like [`Self::get`], with `build` adding headers to each attempt
+    pub async fn send(
+        &self,
+        client: &Client,
+        url: &str,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> reqwest::Result<Response> {
+        let mut last_error = None;
+        for candidate in self.candidates(url) {
+            let result = build(client.get(&candidate))
+                .send()
+                .await
+                .and_then(Response::error_for_status);
+            match result {
+                Ok(response) => return Ok(response),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.expect("candidates() always returns at least one URL"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{response, StubServer};
+
+    const ASSET: &str = "https://resources.download.minecraft.net/a9/a9993e364706816aba3e25717850c26c9cd0d89d";
+
+    fn registry(endpoint: Endpoint, mirrors: &[&str]) -> EndpointRegistry {
+        EndpointRegistry::new(HashMap::from([(
+            endpoint,
+            mirrors.iter().map(|mirror| mirror.to_string()).collect(),
+        )]))
+    }
+
+    #[test]
+    fn without_mirrors_the_official_url_is_used() {
+        assert_eq!(EndpointRegistry::default().candidates(ASSET), vec![ASSET]);
+        assert_eq!(registry(Endpoint::MojangResources, &[]).candidates(ASSET), vec![ASSET]);
+        // Mirrors of another endpoint don't apply
+        assert_eq!(registry(Endpoint::MojangLibraries, &["https://mirror.example"]).candidates(ASSET), vec![ASSET]);
+    }
+
+    #[test]
+    fn mirrors_are_tried_in_order() {
+        let registry = registry(
+            Endpoint::MojangResources,
+            &["https://mirror.example/assets/", "https://resources.download.minecraft.net"],
+        );
+        assert_eq!(
+            registry.candidates(ASSET),
+            vec![
+                "https://mirror.example/assets/a9/a9993e364706816aba3e25717850c26c9cd0d89d",
+                ASSET,
+            ]
+        );
+    }
+
+    #[test]
+    fn alternate_official_hosts_are_mirrored_too() {
+        let registry = registry(Endpoint::MojangMeta, &["https://meta.mirror.example"]);
+        assert_eq!(
+            registry.candidates("https://launchermeta.mojang.com/mc/game/version_manifest_v2.json"),
+            vec!["https://meta.mirror.example/mc/game/version_manifest_v2.json"]
+        );
+    }
+
+    #[test]
+    fn unknown_urls_are_used_as_is() {
+        let registry = registry(Endpoint::MojangResources, &["https://mirror.example"]);
+        let url = "https://cdn.example.com/file.jar";
+        assert_eq!(registry.candidates(url), vec![url]);
+        // Only whole host names match an endpoint
+        let lookalike = "https://resources.download.minecraft.net.example.com/a9";
+        assert_eq!(registry.candidates(lookalike), vec![lookalike]);
+    }
+
+    #[tokio::test]
+    async fn send_falls_through_a_failing_mirror() {
+        let failing = StubServer::start(vec![response("503 Service Unavailable", &[], b"")]).await;
+        let working = StubServer::start(vec![response("200 OK", &[], b"asset")]).await;
+        let registry = registry(Endpoint::MojangResources, &[&failing.base, &working.base]);
+
+        let response = registry.get(&Client::new(), ASSET).await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "asset");
+        assert_eq!(failing.requests().len(), 1);
+        assert!(working.requests()[0].starts_with("get /a9/a9993e364706816aba3e25717850c26c9cd0d89d "));
+    }
+}
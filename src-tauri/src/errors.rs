@@ -32,7 +32,28 @@ pub enum LauncherError {
     
     #[error("Asset processing error: {message}")]
     AssetError { message: String },
+
+    #[error("{} asset(s) failed to download: {}", .failed.len(), .failed.join(", "))]
+    AssetDownloadFailed { failed: Vec<String> },
     
+    #[error("{} file(s) needed to launch are missing: {}", .files.len(), .files.join(", "))]
+    MissingFiles { files: Vec<String> },
+
+    #[error(
+        "Minecraft {version} requires Java {required}, but no compatible installation was found ({}). \
+         Install Java {required} or set java_executables.{required} in the launcher config",
+        describe_found(.found)
+    )]
+    JavaNotFound {
+        version: String,
+        required: u32,
+        /// Executables that were checked, with the Java version they reported
+        found: Vec<String>,
+    },
+
+    #[error("Mojang does not publish Java runtime '{component}' for {platform}")]
+    RuntimeUnavailable { component: String, platform: String },
+
     #[error("File not found: {0}")]
     FileNotFound(String),
     
@@ -47,6 +68,14 @@ pub enum LauncherError {
     },
 }
 
+fn describe_found(found: &[String]) -> String {
+    if found.is_empty() {
+        "no Java found".to_string()
+    } else {
+        format!("found {}", found.join(", "))
+    }
+}
+
 impl From<LauncherError> for String {
     fn from(error: LauncherError) -> Self {
         error.to_string()
@@ -69,6 +98,15 @@ impl From<crate::minecraft_api::LauncherError> for LauncherError {
             crate::minecraft_api::LauncherError::ConfigValidation { field, message } => {
                 LauncherError::ConfigValidation { field, message }
             }
+            crate::minecraft_api::LauncherError::DownloadFailed { url, reason } => {
+                LauncherError::DownloadFailed { url, reason }
+            }
+            crate::minecraft_api::LauncherError::HashMismatch { file, expected, actual } => {
+                LauncherError::HashMismatch { file, expected, actual }
+            }
+            crate::minecraft_api::LauncherError::AssetDownloadFailed { failed } => {
+                LauncherError::AssetDownloadFailed { failed }
+            }
             crate::minecraft_api::LauncherError::FileSystemError { operation, path, error } => {
                 LauncherError::FileSystemError { operation, path, error }
             }
@@ -76,5 +114,16 @@ impl From<crate::minecraft_api::LauncherError> for LauncherError {
     }
 }
 
+impl LauncherError {
+    /// Whether the network itself is unreachable (as opposed to e.g. a bad response),
+    /// which is when launching falls back to offline mode
+    pub fn is_connection_error(&self) -> bool {
+        match self {
+            LauncherError::Network(e) => e.is_connect() || e.is_timeout(),
+            _ => false,
+        }
+    }
+}
+
 // Result type alias for convenience
 pub type LauncherResult<T> = Result<T, LauncherError>;
@@ -0,0 +1,49 @@
+use crate::config::LauncherConfig;
+use crate::downloader::DownloadLimits;
+use crate::minecraft_api::{LauncherError, Result};
+use reqwest::{Certificate, Client, Proxy};
+use std::fs;
+
+/// Sent with every request the launcher makes
+pub const USER_AGENT: &str = concat!(
+    "Satellite-Launcher/",
+    env!("CARGO_PKG_VERSION"),
+    " (contact@satellite-launcher.com)"
+);
+
+/// Build the HTTP client every network call goes through: proxy, extra root
+/// certificates, timeouts and User-Agent all come from the configuration
+pub fn build_client(config: &LauncherConfig) -> Result<Client> {
+    let timeout = DownloadLimits::from_config(config).timeout;
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(timeout)
+        .read_timeout(timeout);
+
+    if let Some(proxy_config) = &config.proxy {
+        let mut proxy = Proxy::all(&proxy_config.url).map_err(|e| LauncherError::ConfigValidation {
+            field: "proxy".to_string(),
+            message: format!("Invalid proxy URL {}: {}", proxy_config.url, e),
+        })?;
+        if let Some(username) = &proxy_config.username {
+            proxy = proxy.basic_auth(username, proxy_config.password.as_deref().unwrap_or_default());
+        }
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ca_bundle) = &config.ca_bundle {
+        let pem = fs::read(ca_bundle).map_err(|e| LauncherError::ConfigValidation {
+            field: "ca_bundle".to_string(),
+            message: format!("Cannot read {}: {}", ca_bundle, e),
+        })?;
+        let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| LauncherError::ConfigValidation {
+            field: "ca_bundle".to_string(),
+            message: format!("Invalid PEM bundle {}: {}", ca_bundle, e),
+        })?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder.build()?)
+}
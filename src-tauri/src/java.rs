@@ -0,0 +1,325 @@
+use crate::config::LauncherConfig;
+use crate::errors::{LauncherError, LauncherResult};
+use crate::minecraft_api::VersionJson;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Name of the Java launcher binary inside a JDK/JRE `bin` directory
+pub const JAVA_BINARY: &str = if cfg!(windows) { "java.exe" } else { "java" };
+
+/// Oldest major version from which Minecraft keeps running on newer Java releases.
+/// Versions requiring Java 8 need exactly that release.
+const FORWARD_COMPATIBLE_FROM: u32 = 16;
+
+/// `1.8.0_382` -> 8, `17.0.9` -> 17, `21` -> 21, `22-ea` -> 22
+pub fn parse_major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        parts.next()?.parse().ok()
+    } else {
+        Some(first)
+    }
+}
+
+/// Where a discovered Java runtime was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JavaSource {
+    /// Set in the launcher configuration
+    Config,
+    JavaHome,
+    Path,
+    /// OS-wide install location such as `/usr/lib/jvm`
+    System,
+    Sdkman,
+    Asdf,
+    Jenv,
+    /// Runtime downloaded by the launcher itself
+    Launcher,
+}
+
+/// A Java runtime found on this machine
+#[derive(Debug, Clone, Serialize)]
+pub struct JavaInstallation {
+    pub path: String,
+    /// Full version string, e.g. `17.0.9` or `1.8.0_382`
+    pub version: String,
+    pub major_version: u32,
+    pub vendor: Option<String>,
+    /// `os.arch` as reported by the JVM, e.g. `amd64` or `aarch64`
+    pub arch: Option<String>,
+    /// 32 or 64, from `sun.arch.data.model`
+    pub bits: Option<u32>,
+    pub source: JavaSource,
+}
+
+/// Directory holding the runtimes the launcher installs for itself
+pub fn runtimes_dir() -> PathBuf {
+    if let Some(data_dir) = dirs::data_dir() {
+        data_dir.join("satellite-launcher").join("runtimes")
+    } else {
+        PathBuf::from("./runtimes")
+    }
+}
+
+/// Probe results by executable, valid while its modification time is unchanged. A launch
+/// checks the config and then selects Java, which would otherwise run every executable twice.
+static PROBES: OnceLock<Mutex<ProbeCache>> = OnceLock::new();
+
+type ProbeCache = HashMap<PathBuf, (SystemTime, Option<JavaInstallation>)>;
+
+/// Run `<executable> -XshowSettings:properties -version` and read version, vendor and
+/// architecture from the system properties it dumps. Results for an unchanged file are reused;
+/// bare names looked up on the PATH are always run.
+pub fn probe(executable: &Path, source: JavaSource) -> Option<JavaInstallation> {
+    let Ok(modified) = fs::metadata(executable).and_then(|meta| meta.modified()) else {
+        return run_probe(executable, source);
+    };
+
+    let probes = PROBES.get_or_init(Default::default);
+    if let Some((probed_at, java)) = probes.lock().unwrap().get(executable) {
+        if *probed_at == modified {
+            return java.clone().map(|java| JavaInstallation { source, ..java });
+        }
+    }
+
+    let java = run_probe(executable, source);
+    probes.lock().unwrap().insert(executable.to_path_buf(), (modified, java.clone()));
+    java
+}
+
+fn run_probe(executable: &Path, source: JavaSource) -> Option<JavaInstallation> {
+    let output = Command::new(executable)
+        .args(["-XshowSettings:properties", "-version"])
+        .output()
+        .ok()?;
+    // Both the settings dump and the version banner go to stderr
+    let text = String::from_utf8_lossy(&output.stderr);
+
+    let property = |key: &str| {
+        text.lines().find_map(|line| {
+            let (name, value) = line.trim().split_once(" = ")?;
+            (name == key).then(|| value.trim().to_string())
+        })
+    };
+
+    let version = property("java.version").or_else(|| text.split('"').nth(1).map(str::to_string))?;
+    let major_version = parse_major_version(&version)?;
+    Some(JavaInstallation {
+        path: executable.to_string_lossy().to_string(),
+        version,
+        major_version,
+        vendor: property("java.vendor"),
+        arch: property("os.arch"),
+        bits: property("sun.arch.data.model").and_then(|bits| bits.parse().ok()),
+        source,
+    })
+}
+
+/// Java executable of a JDK/JRE home, including the macOS bundle layouts
+pub fn executable_in_home(home: &Path) -> Option<PathBuf> {
+    [
+        home.join("bin"),
+        home.join("Contents").join("Home").join("bin"),
+        home.join("jre.bundle").join("Contents").join("Home").join("bin"),
+    ]
+    .into_iter()
+    .map(|bin| bin.join(JAVA_BINARY))
+    .find(|executable| executable.is_file())
+}
+
+/// Java executables of every JDK/JRE home directly inside `dir`
+fn executables_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut homes: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    homes.sort();
+    homes.iter().filter_map(|home| executable_in_home(home)).collect()
+}
+
+/// Directories whose children are Java homes, with the source they count as
+fn install_roots() -> Vec<(PathBuf, JavaSource)> {
+    let mut roots = Vec::new();
+
+    if cfg!(target_os = "linux") {
+        roots.push((PathBuf::from("/usr/lib/jvm"), JavaSource::System));
+        roots.push((PathBuf::from("/usr/lib64/jvm"), JavaSource::System));
+        roots.push((PathBuf::from("/usr/java"), JavaSource::System));
+    } else if cfg!(target_os = "macos") {
+        roots.push((PathBuf::from("/Library/Java/JavaVirtualMachines"), JavaSource::System));
+    } else if cfg!(windows) {
+        for program_files in ["ProgramFiles", "ProgramW6432"] {
+            if let Ok(dir) = std::env::var(program_files) {
+                let dir = PathBuf::from(dir);
+                for vendor in ["Java", "Eclipse Adoptium", "Microsoft", "Zulu", "Amazon Corretto"] {
+                    roots.push((dir.join(vendor), JavaSource::System));
+                }
+            }
+        }
+    }
+
+    if let Some(home) = dirs::home_dir() {
+        let sdkman = std::env::var("SDKMAN_DIR").map(PathBuf::from).unwrap_or_else(|_| home.join(".sdkman"));
+        roots.push((sdkman.join("candidates").join("java"), JavaSource::Sdkman));
+
+        let asdf = std::env::var("ASDF_DATA_DIR").map(PathBuf::from).unwrap_or_else(|_| home.join(".asdf"));
+        roots.push((asdf.join("installs").join("java"), JavaSource::Asdf));
+
+        roots.push((home.join(".jenv").join("versions"), JavaSource::Jenv));
+    }
+
+    roots.push((runtimes_dir(), JavaSource::Launcher));
+    roots
+}
+
+/// Every Java executable worth probing, in order of preference, without duplicates
+fn discovery_candidates() -> Vec<(PathBuf, JavaSource)> {
+    let mut candidates = Vec::new();
+
+    if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+        if let Some(executable) = executable_in_home(Path::new(&java_home)) {
+            candidates.push((executable, JavaSource::JavaHome));
+        }
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path) {
+            let executable = dir.join(JAVA_BINARY);
+            if executable.is_file() {
+                candidates.push((executable, JavaSource::Path));
+            }
+        }
+    }
+    for (root, source) in install_roots() {
+        candidates.extend(executables_in(&root).into_iter().map(|executable| (executable, source)));
+    }
+
+    // `/usr/bin/java`, SDKMAN's `current` and the like are symlinks to runtimes found elsewhere
+    let mut seen = HashSet::new();
+    candidates.retain(|(executable, _)| {
+        seen.insert(fs::canonicalize(executable).unwrap_or_else(|_| executable.clone()))
+    });
+    candidates
+}
+
+/// Find the Java runtimes installed on this machine. Every candidate is probed in
+/// parallel; executables that fail to report a version are left out.
+pub fn discover_java() -> Vec<JavaInstallation> {
+    let candidates = discovery_candidates();
+    std::thread::scope(|scope| {
+        let probes: Vec<_> = candidates
+            .iter()
+            .map(|(executable, source)| scope.spawn(move || probe(executable, *source)))
+            .collect();
+        probes.into_iter().filter_map(|handle| handle.join().ok().flatten()).collect()
+    })
+}
+
+/// Whether Java `actual` can run a version that asks for Java `required`
+pub fn is_compatible(required: u32, actual: u32) -> bool {
+    actual == required || (required >= FORWARD_COMPATIBLE_FROM && actual > required)
+}
+
+/// Executables to consider, most specific first: the per-major override, the configured
+/// executable, `JAVA_HOME`, then `java` on the PATH
+fn candidates(config: &LauncherConfig, required: u32) -> Vec<String> {
+    let mut candidates = Vec::new();
+    if let Some(executable) = config.java_executables.get(&required) {
+        candidates.push(executable.clone());
+    }
+    if let Some(executable) = &config.java_executable {
+        candidates.push(executable.clone());
+    }
+    if let Ok(java_home) = std::env::var("JAVA_HOME") {
+        let executable = PathBuf::from(java_home).join("bin").join(JAVA_BINARY);
+        candidates.push(executable.to_string_lossy().to_string());
+    }
+    candidates.push("java".to_string());
+
+    let mut seen = HashSet::new();
+    candidates.retain(|executable| seen.insert(executable.clone()));
+    candidates
+}
+
+/// Pick the Java executable to launch `version_json` with.
+///
+/// Versions without `javaVersion` use the configured executable as before. Otherwise the
+/// first candidate with exactly the required major version wins, then the first newer one
+/// where that is safe. Runtimes found by [`discover_java`] are only considered when no
+/// configured candidate matches exactly; if none fits, the error names the Java version needed.
+/// Probing runs `java` processes, so it happens on the blocking thread pool.
+pub async fn select_java(config: &LauncherConfig, version_json: &VersionJson) -> LauncherResult<String> {
+    let Some(java_version) = &version_json.java_version else {
+        return Ok(config.get_java_executable());
+    };
+    let required = java_version.major_version;
+
+    let (config, version) = (config.clone(), version_json.id.clone());
+    tokio::task::spawn_blocking(move || select_installed(&config, &version, required))
+        .await
+        .map_err(|e| LauncherError::MinecraftLaunchError {
+            error: format!("Java detection failed: {}", e),
+        })?
+}
+
+fn select_installed(config: &LauncherConfig, version: &str, required: u32) -> LauncherResult<String> {
+    let mut probed: Vec<(String, u32)> = candidates(config, required)
+        .into_iter()
+        .filter_map(|executable| {
+            let java = probe(Path::new(&executable), JavaSource::Config)?;
+            Some((executable, java.major_version))
+        })
+        .collect();
+    if !probed.iter().any(|(_, major)| *major == required) {
+        for installation in discover_java() {
+            if !probed.iter().any(|(executable, _)| *executable == installation.path) {
+                probed.push((installation.path, installation.major_version));
+            }
+        }
+    }
+
+    probed
+        .iter()
+        .find(|(_, major)| *major == required)
+        .or_else(|| probed.iter().find(|(_, major)| is_compatible(required, *major)))
+        .map(|(executable, _)| executable.clone())
+        .ok_or_else(|| LauncherError::JavaNotFound {
+            version: version.to_string(),
+            required,
+            found: probed.iter().map(|(executable, major)| format!("{} (Java {})", executable, major)).collect(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compatibility() {
+        assert!(is_compatible(8, 8));
+        assert!(!is_compatible(8, 17));
+        assert!(is_compatible(16, 17));
+        assert!(is_compatible(17, 21));
+        assert!(!is_compatible(21, 17));
+    }
+
+    #[test]
+    fn candidates_are_unique() {
+        let mut config = LauncherConfig {
+            java_executable: Some("java".to_string()),
+            ..Default::default()
+        };
+        config.java_executables.insert(17, "/opt/jdk-17/bin/java".to_string());
+        config.java_executables.insert(21, "java".to_string());
+
+        let candidates = candidates(&config, 17);
+        assert_eq!(candidates.first().map(String::as_str), Some("/opt/jdk-17/bin/java"));
+        assert_eq!(candidates.iter().filter(|executable| *executable == "java").count(), 1);
+    }
+}
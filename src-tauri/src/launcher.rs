@@ -1,22 +1,80 @@
+use crate::arguments::{self, LaunchVariables};
+use crate::cache::MetadataCache;
 use crate::config::LauncherConfig;
 use crate::downloader::Downloader;
+use crate::endpoints::Endpoint;
 use crate::errors::{LauncherError, LauncherResult};
-use crate::minecraft_api::{self, MinecraftVersion};
-use std::process::Command;
+use crate::java;
+use crate::minecraft_api::{self, MinecraftVersion, RuleContext};
+use crate::paths::{self, GameDirectory};
+use crate::runtime;
+use futures::stream::{self, StreamExt};
+use crate::store::StoreGcReport;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use sysinfo::{Pid, ProcessesToUpdate, System};
 use tauri::Emitter;
 use crate::mods::ModManager;
 
+/// Written into a natives directory once the game is running; holds the game's pid
+const NATIVES_PID_FILE: &str = ".pid";
+
 pub struct MinecraftLauncher {
     downloader: Downloader,
 }
 
-impl MinecraftLauncher {
-    pub fn new() -> Self {
-        Self {
-            downloader: Downloader::new(),
+/// A file that game file verification knows how to check and re-download
+struct CheckedFile {
+    url: String,
+    path: PathBuf,
+    sha1: Option<String>,
+    size: Option<u64>,
+}
+
+impl CheckedFile {
+    /// Cheap check used for progress planning: exists with the expected size
+    fn is_present(&self) -> bool {
+        match std::fs::metadata(&self.path) {
+            Ok(meta) => self.size.is_none_or(|size| meta.len() == size),
+            Err(_) => false,
         }
     }
 
+    /// Full check: expected size and SHA-1. Blocks while hashing.
+    fn is_intact(&self) -> bool {
+        self.is_present()
+            && self.sha1.as_deref().is_none_or(|expected| {
+                minecraft_api::sha1_file(&self.path).is_ok_and(|actual| actual.eq_ignore_ascii_case(expected))
+            })
+    }
+}
+
+/// Outcome of verifying a version's game files
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerificationReport {
+    pub checked: usize,
+    /// Files that were missing or failed the size/SHA-1 check
+    pub broken: Vec<String>,
+    /// Installed version JSONs used as they are: the manifest checksum only covers a
+    /// download, so local edits (or a loader profile, which has none) are not detected
+    pub trusted: Vec<String>,
+    pub repaired: Vec<String>,
+    /// Broken files that could not be re-downloaded
+    pub failed: Vec<String>,
+}
+
+impl MinecraftLauncher {
+    /// Fails with `ConfigValidation` when the network settings can't be applied, rather
+    /// than silently downloading without the configured proxy
+    pub fn new() -> LauncherResult<Self> {
+        Ok(Self {
+            downloader: Downloader::from_config(&LauncherConfig::load().unwrap_or_default())?,
+        })
+    }
+
     /// Launch Minecraft with the specified version
     pub async fn launch_version(
         &self,
@@ -28,20 +86,53 @@ impl MinecraftLauncher {
             message: e.to_string(),
         })?;
 
-        let base_dir = &config.game_directory;
+        // Catch a broken Java path here rather than as a raw OS error from spawn. The check
+        // runs the configured Java executables, so it happens on the blocking thread pool.
+        let checked = config.clone();
+        let issues = tokio::task::spawn_blocking(move || checked.check())
+            .await
+            .map_err(|e| LauncherError::ConfigValidation {
+                field: "config".to_string(),
+                message: e.to_string(),
+            })?;
+        for issue in issues {
+            if !issue.warning {
+                return Err(LauncherError::ConfigValidation {
+                    field: issue.field,
+                    message: issue.message,
+                });
+            }
+            window.emit("log", format!("⚠️ {}: {}", issue.field, issue.message)).ok();
+        }
+
+        let game_dir = GameDirectory::new(&config.game_directory);
 
         // Check if mods are installed to determine if we need Fabric
-        let mod_manager = ModManager::new(base_dir);
+        let mod_manager = ModManager::new(&config.game_directory);
         let installed_mods = mod_manager.get_installed_mods().unwrap_or_default();
         let needs_fabric = !installed_mods.is_empty();
 
-        if needs_fabric {
-            window.emit("log", format!("🔧 {} mods detected! Setting up Fabric automatically...", installed_mods.len())).ok();
-            self.ensure_fabric_installed(&version, base_dir, &window).await?;
-            self.launch_with_fabric(&config, &version, base_dir, &window).await?;
+        if config.offline_mode {
+            self.launch_offline(&config, &version, needs_fabric, &game_dir, &window).await?;
         } else {
-            window.emit("log", "🎮 No mods detected, launching vanilla Minecraft...").ok();
-            self.launch_vanilla(&config, &version, base_dir, &window).await?;
+            let result = if needs_fabric {
+                window.emit("log", format!("🔧 {} mods detected! Setting up Fabric automatically...", installed_mods.len())).ok();
+                match self.ensure_fabric_installed(&version, &game_dir, &window).await {
+                    Ok(()) => self.launch_with_fabric(&config, &version, &game_dir, &window).await,
+                    Err(e) => Err(e),
+                }
+            } else {
+                window.emit("log", "🎮 No mods detected, launching vanilla Minecraft...").ok();
+                self.launch_vanilla(&config, &version, &game_dir, &window).await
+            };
+
+            match result {
+                Err(e) if e.is_connection_error() => {
+                    window.emit("log", format!("📴 Network unavailable ({}), falling back to offline mode", e)).ok();
+                    self.launch_offline(&config, &version, needs_fabric, &game_dir, &window).await?;
+                }
+                other => other?,
+            }
         }
 
         window
@@ -51,49 +142,368 @@ impl MinecraftLauncher {
         Ok(())
     }
 
+    /// Rehash every asset object, library and the client jar of a version, then re-download
+    /// only the files that are missing or don't match the index/version JSON
+    pub async fn verify_game_files(
+        &self,
+        window: tauri::Window,
+        version: String,
+    ) -> LauncherResult<VerificationReport> {
+        let config = LauncherConfig::load().map_err(|e| LauncherError::ConfigValidation {
+            field: "config".to_string(),
+            message: e.to_string(),
+        })?;
+        let game_dir = GameDirectory::new(&config.game_directory);
+
+        let trusted = installed_version_chain(&game_dir, &version);
+        for path in &trusted {
+            window
+                .emit("log", format!("Using installed version JSON without a checksum: {}", path.display()))
+                .ok();
+        }
+
+        // Offline, only what is installed can be checked and nothing can be repaired
+        let version_json = if config.offline_mode {
+            minecraft_api::resolve_version(&game_dir, &version)?
+        } else {
+            self.load_version_json(&config, &version, &game_dir, &window).await?
+        };
+        let files = self
+            .collect_game_files(&version_json, &game_dir, config.offline_mode, &window)
+            .await?;
+
+        window.emit("log", format!("Verifying {} files...", files.len())).ok();
+        let total = files.len().max(1);
+        let mut report = VerificationReport {
+            checked: files.len(),
+            trusted: trusted.iter().map(|path| path.to_string_lossy().to_string()).collect(),
+            ..Default::default()
+        };
+        let mut broken = Vec::new();
+
+        // Hash on the blocking pool, one file per core, so the async runtime stays responsive
+        let hashers = std::thread::available_parallelism().map_or(4, |n| n.get());
+        let mut checks = stream::iter(files)
+            .map(|file| async move {
+                tokio::task::spawn_blocking(move || {
+                    let intact = file.is_intact();
+                    (file, intact)
+                })
+                .await
+            })
+            .buffer_unordered(hashers);
+
+        let mut done = 0;
+        while let Some(checked) = checks.next().await {
+            let (file, intact) = checked.map_err(|e| LauncherError::FileSystemError {
+                operation: "verify_file".to_string(),
+                path: version.clone(),
+                error: e.to_string(),
+            })?;
+            if intact {
+                // Just rehashed, so safe to seed the shared store with
+                self.downloader.share(&file.path, file.sha1.as_deref());
+            } else {
+                window.emit("log", format!("Broken file: {}", file.path.display())).ok();
+                report.broken.push(file.path.to_string_lossy().to_string());
+                broken.push(file);
+            }
+            done += 1;
+            window.emit("progress", done as f64 / total as f64 * 0.5).ok();
+        }
+
+        if broken.is_empty() {
+            window.emit("progress", 1.0).ok();
+            window.emit("log", "✅ All game files verified").ok();
+            return Ok(report);
+        }
+
+        if config.offline_mode {
+            window
+                .emit("log", format!("📴 Offline mode: {} broken files left unrepaired", broken.len()))
+                .ok();
+            window.emit("progress", 1.0).ok();
+            report.failed = report.broken.clone();
+            return Ok(report);
+        }
+
+        window.emit("log", format!("Repairing {} broken files...", broken.len())).ok();
+        let total = broken.len();
+        let mut repairs = stream::iter(broken)
+            .map(|file| async move {
+                // Remove first so verify_and_download doesn't rehash a file we know is bad
+                let _ = std::fs::remove_file(&file.path);
+                let result = self
+                    .downloader
+                    .verify_and_download(&file.url, &file.path, file.sha1.as_deref(), file.size, None)
+                    .await;
+                (file.path, result)
+            })
+            .buffer_unordered(self.downloader.limits().concurrent_downloads);
+
+        let mut done = 0;
+        while let Some((path, result)) = repairs.next().await {
+            done += 1;
+            window.emit("progress", 0.5 + done as f64 / total as f64 * 0.5).ok();
+            let path = path.to_string_lossy().to_string();
+            match result {
+                Ok(_) => {
+                    window.emit("log", format!("Repaired: {}", path)).ok();
+                    report.repaired.push(path);
+                }
+                Err(e) => {
+                    window.emit("log", format!("Failed to repair {}: {}", path, e)).ok();
+                    report.failed.push(path);
+                }
+            }
+        }
+
+        window
+            .emit(
+                "log",
+                format!(
+                    "Verification finished: {} broken, {} repaired, {} failed",
+                    report.broken.len(),
+                    report.repaired.len(),
+                    report.failed.len()
+                ),
+            )
+            .ok();
+
+        Ok(report)
+    }
+
+    /// List the client jar, libraries, natives and asset objects a version needs.
+    /// The asset index itself is fetched (and repaired) first since it lists the objects;
+    /// `offline`, it is checked like the other files and its objects listed only if it parses.
+    async fn collect_game_files(
+        &self,
+        version_json: &minecraft_api::VersionJson,
+        game_dir: &GameDirectory,
+        offline: bool,
+        window: &tauri::Window,
+    ) -> LauncherResult<Vec<CheckedFile>> {
+        let context = RuleContext::current();
+        let mut files = Vec::new();
+
+        if let Some(downloads) = &version_json.downloads {
+            files.push(CheckedFile {
+                url: downloads.client.url.clone(),
+                path: game_dir.version_jar_path(version_json.jar_id()),
+                sha1: downloads.client.sha1.clone(),
+                size: downloads.client.size,
+            });
+        }
+
+        for lib in version_json.libraries.iter().filter(|lib| lib.is_allowed(&context)) {
+            if let (Some(path), Some(url)) = (lib.artifact_path(), lib.artifact_url()) {
+                files.push(CheckedFile {
+                    url,
+                    path: game_dir.library_path(&path),
+                    sha1: lib.artifact_sha1().map(str::to_string),
+                    size: lib.artifact_size(),
+                });
+            }
+            if let Some(native) = lib.native_download(&context) {
+                if let Some(path) = &native.path {
+                    files.push(CheckedFile {
+                        url: native.url.clone(),
+                        path: game_dir.library_path(path),
+                        sha1: native.sha1.clone(),
+                        size: native.size,
+                    });
+                }
+            }
+        }
+
+        if let Some(asset_index) = &version_json.asset_index {
+            let index_path = game_dir.asset_index_path(&asset_index.id);
+            if offline {
+                files.push(CheckedFile {
+                    url: asset_index.url.clone(),
+                    path: index_path.clone(),
+                    sha1: asset_index.sha1.clone(),
+                    size: asset_index.size,
+                });
+            } else {
+                self.downloader
+                    .verify_and_download(
+                        &asset_index.url,
+                        &index_path,
+                        asset_index.sha1.as_deref(),
+                        asset_index.size,
+                        Some(window),
+                    )
+                    .await?;
+            }
+
+            let index_str = match std::fs::read_to_string(&index_path) {
+                Ok(index_str) => index_str,
+                Err(_) if offline => return Ok(files),
+                Err(e) => {
+                    return Err(LauncherError::FileSystemError {
+                        operation: "read_asset_index".to_string(),
+                        path: index_path.to_string_lossy().to_string(),
+                        error: e.to_string(),
+                    })
+                }
+            };
+            let index = match minecraft_api::parse_asset_index(&index_str) {
+                Ok(index) => index,
+                Err(_) if offline => return Ok(files),
+                Err(e) => return Err(e.into()),
+            };
+
+            // Names sharing a hash share the file; repairing it twice at once would race
+            let mut hashes = HashSet::new();
+            for object in index.objects.into_values().filter(|object| hashes.insert(object.hash.clone())) {
+                files.push(CheckedFile {
+                    url: minecraft_api::asset_object_url(&object.hash),
+                    path: game_dir.asset_object_path(&object.hash),
+                    sha1: Some(object.hash),
+                    size: Some(object.size),
+                });
+            }
+        }
+
+        Ok(files)
+    }
+
     /// Download all required libraries
     async fn download_libraries(
         &self,
         libraries: &[minecraft_api::Library],
-        base_dir: &str,
+        game_dir: &GameDirectory,
         window: &tauri::Window,
     ) -> LauncherResult<()> {
-        for lib in libraries {
-            if let Some(downloads) = &lib.downloads {
-                if let Some(artifact) = &downloads.artifact {
-                    if let Some(lib_path_rel) = &artifact.path {
-                        let lib_path = format!("{}/libraries/{}", base_dir, lib_path_rel);
-                        let expected_sha1 = artifact.sha1.as_deref();
-                        
-                        self.downloader
-                            .verify_and_download(&artifact.url, &lib_path, expected_sha1, Some(window))
-                            .await?;
-                    }
-                }
+        let context = RuleContext::current();
+
+        for lib in libraries.iter().filter(|lib| lib.is_allowed(&context)) {
+            if let (Some(lib_path_rel), Some(url)) = (lib.artifact_path(), lib.artifact_url()) {
+                let lib_path = game_dir.library_path(&lib_path_rel);
+
+                self.downloader
+                    .verify_and_download(&url, &lib_path, lib.artifact_sha1(), lib.artifact_size(), Some(window))
+                    .await?;
             }
         }
         Ok(())
     }
 
+    /// Download native library jars for this OS and extract them into `natives_dir`
+    async fn prepare_natives(
+        &self,
+        libraries: &[minecraft_api::Library],
+        game_dir: &GameDirectory,
+        natives_dir: &Path,
+        window: &tauri::Window,
+    ) -> LauncherResult<()> {
+        let context = RuleContext::current();
+
+        for lib in libraries.iter().filter(|lib| lib.is_allowed(&context)) {
+            if let Some(native) = lib.native_download(&context) {
+                if let Some(native_path_rel) = &native.path {
+                    let native_path = game_dir.library_path(native_path_rel);
+
+                    self.downloader
+                        .verify_and_download(&native.url, &native_path, native.sha1.as_deref(), native.size, Some(window))
+                        .await?;
+                }
+            }
+        }
+
+        extract_all_natives(libraries, game_dir, natives_dir, window)
+    }
+
+    /// Launch from local files only: no manifest, no downloads. Fails with the list of
+    /// missing files when the classpath, client jar, natives or asset index are incomplete.
+    async fn launch_offline(
+        &self,
+        config: &LauncherConfig,
+        version: &str,
+        needs_fabric: bool,
+        game_dir: &GameDirectory,
+        window: &tauri::Window,
+    ) -> LauncherResult<()> {
+        window.emit("log", "📴 Offline mode: launching from local files only").ok();
+
+        let version_id = match find_fabric_version(game_dir, version) {
+            Some(fabric_id) if needs_fabric => fabric_id,
+            None if needs_fabric => {
+                window.emit("log", "No local Fabric installation found, launching vanilla").ok();
+                version.to_string()
+            }
+            _ => version.to_string(),
+        };
+
+        let version_json_path = game_dir.version_json_path(&version_id);
+        if !version_json_path.exists() {
+            return Err(LauncherError::MissingFiles {
+                files: vec![version_json_path.to_string_lossy().to_string()],
+            });
+        }
+        let version_json = minecraft_api::resolve_version(game_dir, &version_id)?;
+
+        let missing = missing_launch_files(&version_json, game_dir);
+        if !missing.is_empty() {
+            for file in &missing {
+                window.emit("log", format!("Missing: {}", file)).ok();
+            }
+            return Err(LauncherError::MissingFiles { files: missing });
+        }
+
+        let natives_dir = game_dir.new_natives_dir(&version_json.id);
+        extract_all_natives(&version_json.libraries, game_dir, &natives_dir, window)?;
+
+        let jar_path = game_dir.version_jar_path(version_json.jar_id());
+        self.launch_minecraft(config, &version_json, &jar_path, &natives_dir, game_dir, window).await
+    }
+
+    /// Download the client jar, or make sure a local one exists for versions that don't list it
+    async fn download_client_jar(
+        &self,
+        version_json: &minecraft_api::VersionJson,
+        jar_path: &Path,
+        window: &tauri::Window,
+    ) -> LauncherResult<()> {
+        match &version_json.downloads {
+            Some(downloads) => {
+                let client = &downloads.client;
+                self.downloader
+                    .verify_and_download(&client.url, jar_path, client.sha1.as_deref(), client.size, Some(window))
+                    .await?;
+                Ok(())
+            }
+            None if jar_path.exists() => Ok(()),
+            None => Err(LauncherError::FileNotFound(jar_path.to_string_lossy().to_string())),
+        }
+    }
+
     /// Download game assets
     async fn download_assets(
         &self,
         asset_index: &minecraft_api::AssetIndex,
-        base_dir: &str,
+        game_dir: &GameDirectory,
         window: &tauri::Window,
     ) -> LauncherResult<()> {
-        let assets_index_path = format!("{}/assets/indexes/{}.json", base_dir, asset_index.id);
-        
+        let assets_index_path = game_dir.asset_index_path(&asset_index.id);
+
         // Download asset index
         self.downloader
-            .download_file_with_progress(&asset_index.url, &assets_index_path, Some(window))
+            .verify_and_download(
+                &asset_index.url,
+                &assets_index_path,
+                asset_index.sha1.as_deref(),
+                asset_index.size,
+                Some(window),
+            )
             .await?;
 
         // Download individual assets
         let asset_futures = minecraft_api::download_assets(
-            self.downloader.client(),
-            &assets_index_path,
-            base_dir,
+            &self.downloader,
+            &asset_index.id,
+            game_dir,
             window.clone(),
         );
         
@@ -106,137 +516,222 @@ impl MinecraftLauncher {
         &self,
         config: &LauncherConfig,
         version_json: &minecraft_api::VersionJson,
-        selected_version: &MinecraftVersion,
-        jar_path: &str,
-        base_dir: &str,
+        jar_path: &Path,
+        natives_dir: &Path,
+        game_dir: &GameDirectory,
+        window: &tauri::Window,
     ) -> LauncherResult<()> {
         // Build classpath
-        let mut classpath = Vec::new();
-        
-        for lib in &version_json.libraries {
-            if let Some(downloads) = &lib.downloads {
-                if let Some(artifact) = &downloads.artifact {
-                    if let Some(lib_path_rel) = &artifact.path {
-                        let lib_path = format!("{}/libraries/{}", base_dir, lib_path_rel);
-                        classpath.push(lib_path);
-                    }
-                }
+        let context = RuleContext::current();
+        let classpath = paths::build_classpath(&version_json.libraries, game_dir, jar_path, &context);
+
+        let mut variables = LaunchVariables::new(config, game_dir);
+        variables.version_name = version_json.id.clone();
+        variables.version_type = version_json.r#type.clone().unwrap_or_else(|| "release".to_string());
+        variables.assets_index_name = version_json.asset_index_id().to_string();
+        // Pre-1.7 versions read assets by name from the virtual/resources tree
+        if let Ok(index) = minecraft_api::read_asset_index(game_dir, version_json.asset_index_id()) {
+            if let Some(dir) = index.legacy_assets_dir(game_dir, version_json.asset_index_id()) {
+                variables.game_assets = dir.to_string_lossy().to_string();
             }
         }
-        
-        classpath.push(jar_path.to_string());
-        let classpath_str = classpath.join(";");
+        variables.natives_directory = natives_dir.to_string_lossy().to_string();
+        variables.classpath = paths::join_classpath(&classpath);
 
-        // Prepare Java command
-        let java_executable = config.get_java_executable();
-        let java_args = config.get_jvm_args();
-        
-        let mut command = Command::new(&java_executable);
-        
-        // Add JVM arguments
-        for arg in java_args {
-            command.arg(arg);
-        }
-
-        // Add Minecraft arguments
-        let _output = command
-            .arg("-cp")
-            .arg(&classpath_str)
-            .arg(&version_json.main_class)
-            .arg("--username")
-            .arg(&config.player_name)
-            .arg("--version")
-            .arg(&selected_version.id)
-            .arg("--gameDir")
-            .arg(base_dir)
-            .arg("--assetsDir")
-            .arg(format!("{}/assets", base_dir))
-            .arg("--assetIndex")
-            .arg(&version_json.asset_index.id)
-            .arg("--uuid")
-            .arg(&config.player_uuid.clone().unwrap_or_default())
-            .arg("--accessToken")
-            .arg("N/A")
-            .arg("--userType")
-            .arg("legacy")
-            .arg("--versionType")
-            .arg(&selected_version.r#type)
-            .current_dir(base_dir)
-            .spawn()
-            .map_err(|e| LauncherError::MinecraftLaunchError {
-                error: e.to_string(),
-            })?;
+        let jvm_args = arguments::jvm_arguments(version_json, &context, &variables);
+        let game_args = arguments::game_arguments(version_json, &context, &variables);
 
+        let java_executable = self.select_java(config, version_json, window).await?;
+        let child = self.spawn_minecraft(config, &java_executable, jvm_args, &version_json.main_class, game_args, game_dir)?;
+        release_natives_on_exit(child, natives_dir);
         Ok(())
     }
 
-    /// Get available Minecraft versions
+    /// Pick the Java executable for `version_json`, installing Mojang's runtime for its
+    /// `javaVersion.component` when no suitable JVM is installed
+    async fn select_java(
+        &self,
+        config: &LauncherConfig,
+        version_json: &minecraft_api::VersionJson,
+        window: &tauri::Window,
+    ) -> LauncherResult<String> {
+        let not_found = match java::select_java(config, version_json).await {
+            Err(e @ LauncherError::JavaNotFound { .. }) => e,
+            result => return result,
+        };
+        let Some(component) = version_json.java_version.as_ref().and_then(|java| java.component.as_deref()) else {
+            return Err(not_found);
+        };
+        if config.offline_mode {
+            return runtime::installed_executable(component)
+                .map(|executable| executable.to_string_lossy().to_string())
+                .ok_or(not_found);
+        }
+
+        window
+            .emit("log", format!("No suitable Java found, using Mojang runtime {}", component))
+            .ok();
+        match runtime::install_runtime(&self.downloader, config, component, window).await {
+            Ok(executable) => Ok(executable.to_string_lossy().to_string()),
+            Err(e) => {
+                window.emit("log", format!("Failed to install Java runtime {}: {}", component, e)).ok();
+                // Offline, a previous install is still good enough
+                runtime::installed_executable(component)
+                    .map(|executable| executable.to_string_lossy().to_string())
+                    .ok_or(not_found)
+            }
+        }
+    }
+
+    /// Spawn the Java process with the configured memory settings and the expanded arguments
+    fn spawn_minecraft(
+        &self,
+        config: &LauncherConfig,
+        java_executable: &str,
+        jvm_args: Vec<String>,
+        main_class: &str,
+        game_args: Vec<String>,
+        game_dir: &GameDirectory,
+    ) -> LauncherResult<Child> {
+        let mut command = Command::new(java_executable);
+        command
+            .args(config.get_jvm_args())
+            .args(jvm_args)
+            .arg(main_class)
+            .args(game_args)
+            .current_dir(game_dir.root());
+
+        command.spawn().map_err(|e| LauncherError::MinecraftLaunchError {
+            error: e.to_string(),
+        })
+    }
+
+    /// Remove shared store objects that no installed version references. The configured
+    /// game directory is always scanned, along with any `game_directories` given.
+    pub fn collect_store_garbage(&self, game_directories: &[String]) -> LauncherResult<StoreGcReport> {
+        let Some(store) = self.downloader.store() else {
+            return Ok(StoreGcReport::default());
+        };
+        let config = LauncherConfig::load().map_err(|e| LauncherError::ConfigValidation {
+            field: "config".to_string(),
+            message: e.to_string(),
+        })?;
+
+        let mut referenced = HashSet::new();
+        for dir in std::iter::once(&config.game_directory).chain(game_directories) {
+            collect_referenced_hashes(&GameDirectory::new(dir), &mut referenced);
+        }
+
+        store.collect_garbage(&referenced).map_err(|e| LauncherError::FileSystemError {
+            operation: "collect_store_garbage".to_string(),
+            path: store.root().to_string_lossy().to_string(),
+            error: e.to_string(),
+        })
+    }
+
+    /// Get available Minecraft versions: the remote manifest plus anything installed locally
     pub async fn get_versions(&self) -> LauncherResult<Vec<MinecraftVersion>> {
-        let manifest_json = minecraft_api::download_version_manifest(self.downloader.client()).await?;
-        let manifest = minecraft_api::parse_version_manifest(&manifest_json)?;
-        
-        let versions = manifest
-            .versions
-            .iter()
-            .map(|v| MinecraftVersion {
-                id: v.id.clone(),
-                r#type: v.r#type.clone(),
-                url: v.url.clone(),
-            })
-            .collect();
-            
-        Ok(versions)
+        let config = LauncherConfig::load().map_err(|e| LauncherError::ConfigValidation {
+            field: "config".to_string(),
+            message: e.to_string(),
+        })?;
+        let game_dir = GameDirectory::new(&config.game_directory);
+        let local = minecraft_api::scan_local_versions(&game_dir);
+        if config.offline_mode {
+            return Ok(local);
+        }
+        let cache = MetadataCache::from_config(&config);
+
+        let remote = match minecraft_api::download_version_manifest(self.downloader.client(), &cache).await {
+            Ok(manifest_json) => minecraft_api::parse_version_manifest(&manifest_json)?.versions,
+            // Without a manifest we can still offer what is installed
+            Err(_) if !local.is_empty() => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(minecraft_api::merge_versions(remote, local))
     }
 
-    /// Launch vanilla Minecraft (original implementation)
-    async fn launch_vanilla(
+    /// Load the effective version JSON, preferring a local `versions/<id>/<id>.json` over the manifest
+    async fn load_version_json(
         &self,
         config: &LauncherConfig,
         version: &str,
-        base_dir: &str,
+        game_dir: &GameDirectory,
         window: &tauri::Window,
-    ) -> LauncherResult<()> {
-        // Step 1: Download and parse version manifest
+    ) -> LauncherResult<minecraft_api::VersionJson> {
+        if game_dir.version_json_path(version).exists() {
+            window.emit("log", format!("Using installed version JSON: {}", version)).ok();
+            return Ok(minecraft_api::resolve_version(game_dir, version)?);
+        }
+
+        // Download and parse version manifest
         window.emit("log", "Downloading version manifest...").ok();
-        let manifest_json = minecraft_api::download_version_manifest(self.downloader.client()).await?;
+        let cache = MetadataCache::from_config(config);
+        let manifest_json = minecraft_api::download_version_manifest(self.downloader.client(), &cache).await?;
         let manifest = minecraft_api::parse_version_manifest(&manifest_json)?;
 
-        // Step 2: Find the selected version
+        // Find the selected version
         let selected_version = manifest
             .versions
             .iter()
             .find(|v| v.id == version)
             .ok_or_else(|| LauncherError::VersionNotFound { version: version.to_string() })?;
 
-        // Step 3: Download version JSON
+        // Download version JSON
         window
             .emit("log", format!("Downloading version JSON: {}", selected_version.id))
             .ok();
         let version_json_str =
-            minecraft_api::download_version_json(self.downloader.client(), &selected_version.url).await?;
+            minecraft_api::download_version_json(
+                self.downloader.client(),
+                &cache,
+                &selected_version.url,
+                selected_version.sha1.as_deref(),
+            )
+            .await?;
         let version_json = minecraft_api::parse_version_json(&version_json_str)?;
 
-        // Step 4: Download main JAR
-        window.emit("log", "Downloading main JAR...").ok();
-        let jar_path = format!(
-            "{}/versions/{}/{}.jar",
-            base_dir, selected_version.id, selected_version.id
-        );
-        self.downloader
-            .download_file_with_progress(&version_json.downloads.client.url, &jar_path, Some(window))
-            .await?;
+        // Create version directory
+        let version_dir = game_dir.version_dir(&selected_version.id);
+        std::fs::create_dir_all(&version_dir).map_err(|e| LauncherError::FileSystemError {
+            operation: "create_version_dir".to_string(),
+            path: version_dir.to_string_lossy().to_string(),
+            error: e.to_string(),
+        })?;
+        
+        // Save version JSON so later launches work from the local copy
+        let version_json_path = game_dir.version_json_path(&selected_version.id);
+        std::fs::write(&version_json_path, &version_json_str).map_err(|e| LauncherError::FileSystemError {
+            operation: "save_version_json".to_string(),
+            path: version_json_path.to_string_lossy().to_string(),
+            error: e.to_string(),
+        })?;
 
-        // Step 5: Download libraries
-        window.emit("log", "Downloading libraries...").ok();
-        self.download_libraries(&version_json.libraries, base_dir, window).await?;
+        Ok(version_json)
+    }
 
-        // Step 6: Download assets
-        window.emit("log", "Downloading assets...").ok();
-        self.download_assets(&version_json.asset_index, base_dir, window).await?;
+    /// Launch vanilla Minecraft, or any other installed version
+    async fn launch_vanilla(
+        &self,
+        config: &LauncherConfig,
+        version: &str,
+        game_dir: &GameDirectory,
+        window: &tauri::Window,
+    ) -> LauncherResult<()> {
+        // Steps 1-4: Version JSON, main JAR, libraries and assets
+        let version_json = self.download_vanilla_resources(config, version, game_dir, window).await?;
+
+        // Step 5: Extract natives
+        window.emit("log", "Extracting natives...").ok();
+        let natives_dir = game_dir.new_natives_dir(&version_json.id);
+        self.prepare_natives(&version_json.libraries, game_dir, &natives_dir, window).await?;
+        self.downloader.progress().complete();
 
-        // Step 7: Launch Minecraft
+        // Step 6: Launch Minecraft
         window.emit("log", "All downloads complete! Launching Minecraft...").ok();
-        self.launch_minecraft(config, &version_json, selected_version, &jar_path, base_dir).await?;
+        let jar_path = game_dir.version_jar_path(version_json.jar_id());
+        self.launch_minecraft(config, &version_json, &jar_path, &natives_dir, game_dir, window).await?;
 
         Ok(())
     }
@@ -245,52 +740,18 @@ impl MinecraftLauncher {
     async fn ensure_fabric_installed(
         &self,
         minecraft_version: &str,
-        base_dir: &str,
+        game_dir: &GameDirectory,
         window: &tauri::Window,
     ) -> LauncherResult<()> {
-        // Check if any Fabric version is already installed for this Minecraft version
-        let versions_dir = format!("{}/versions", base_dir);
-        if let Ok(entries) = std::fs::read_dir(&versions_dir) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let name = entry.file_name().to_string_lossy().to_string();
-                if name.starts_with("fabric-loader-") && name.ends_with(&format!("-{}", minecraft_version)) {
-                    let fabric_profile_path = format!("{}/versions/{}/{}.json", base_dir, name, name);
-                    if std::path::Path::new(&fabric_profile_path).exists() {
-                        // Check if Fabric libraries are also downloaded
-                        if let Ok(profile_content) = std::fs::read_to_string(&fabric_profile_path) {
-                            if let Ok(profile) = serde_json::from_str::<serde_json::Value>(&profile_content) {
-                                if let Some(libraries) = profile["libraries"].as_array() {
-                                    let mut all_libs_exist = true;
-                                    for lib in libraries {
-                                        if let Some(name) = lib["name"].as_str() {
-                                            // Convert Maven coordinates to path: group:artifact:version -> group/artifact/version/artifact-version.jar
-                                            let parts: Vec<&str> = name.split(':').collect();
-                                            if parts.len() == 3 {
-                                                let group = parts[0].replace('.', "/");
-                                                let artifact = parts[1];
-                                                let version = parts[2];
-                                                
-                                                let path = format!("{}/{}/{}/{}-{}.jar", group, artifact, version, artifact, version);
-                                                let lib_path = format!("{}/libraries/{}", base_dir, path);
-                                                
-                                                if !std::path::Path::new(&lib_path).exists() {
-                                                    all_libs_exist = false;
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                    }
-                                    if all_libs_exist {
-                                        window.emit("log", format!("✅ Fabric already installed: {}", name)).ok();
-                                        return Ok(());
-                                    } else {
-                                        window.emit("log", format!("🔄 Fabric profile exists but libraries missing, re-downloading...")).ok();
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
+        // Libraries of an installed profile are fetched with the merged profile at launch
+        if let Some(name) = find_fabric_version(game_dir, minecraft_version) {
+            match minecraft_api::read_local_version_json(game_dir, &name) {
+                Ok(_) => {
+                    window.emit("log", format!("✅ Fabric already installed: {}", name)).ok();
+                    return Ok(());
+                }
+                Err(e) => {
+                    window.emit("log", format!("🔄 Fabric profile {} is unreadable ({}), reinstalling...", name, e)).ok();
                 }
             }
         }
@@ -298,7 +759,7 @@ impl MinecraftLauncher {
         window.emit("log", "📦 Installing Fabric Loader automatically...").ok();
         
         // Download Fabric installer
-        self.download_and_install_fabric(minecraft_version, base_dir, window).await?;
+        self.download_and_install_fabric(minecraft_version, game_dir, window).await?;
         
         window.emit("log", "✅ Fabric installed successfully!").ok();
         Ok(())
@@ -308,24 +769,20 @@ impl MinecraftLauncher {
     async fn download_and_install_fabric(
         &self,
         minecraft_version: &str,
-        base_dir: &str,
+        game_dir: &GameDirectory,
         window: &tauri::Window,
     ) -> LauncherResult<()> {
         // Get latest Fabric loader version
-        let fabric_api_url = "https://meta.fabricmc.net/v2/versions/loader";
-        let response = self.downloader.client()
-            .get(fabric_api_url)
-            .send()
+        let fabric_api_url = Endpoint::FabricMeta.url("v2/versions/loader");
+        let response = self.downloader
+            .get(&fabric_api_url)
             .await
-            .map_err(|e| LauncherError::DownloadFailed {
-                url: fabric_api_url.to_string(),
-                reason: e.to_string(),
-            })?;
+            .map_err(|e| minecraft_api::download_error(&fabric_api_url, e))?;
 
-        let fabric_versions: serde_json::Value = response.json().await.map_err(|e| LauncherError::DownloadFailed {
-            url: fabric_api_url.to_string(),
-            reason: e.to_string(),
-        })?;
+        let fabric_versions: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| minecraft_api::download_error(&fabric_api_url, e))?;
 
         let latest_loader = fabric_versions
             .as_array()
@@ -336,21 +793,17 @@ impl MinecraftLauncher {
         window.emit("log", format!("📋 Using Fabric Loader version: {}", latest_loader)).ok();
 
         // Download Fabric profile JSON
-        let profile_url = format!(
-            "https://meta.fabricmc.net/v2/versions/loader/{}/{}/profile/json",
+        let profile_url = Endpoint::FabricMeta.url(&format!(
+            "v2/versions/loader/{}/{}/profile/json",
             minecraft_version, latest_loader
-        );
+        ));
 
         window.emit("log", "⬇️ Downloading Fabric profile...").ok();
         
-        let profile_response = self.downloader.client()
+        let profile_response = self.downloader
             .get(&profile_url)
-            .send()
             .await
-            .map_err(|e| LauncherError::DownloadFailed {
-                url: profile_url.clone(),
-                reason: e.to_string(),
-            })?;
+            .map_err(|e| minecraft_api::download_error(&profile_url, e))?;
 
         if !profile_response.status().is_success() {
             return Err(LauncherError::DownloadFailed {
@@ -359,93 +812,33 @@ impl MinecraftLauncher {
             });
         }
 
-        let profile_json = profile_response.text().await.map_err(|e| LauncherError::DownloadFailed {
-            url: profile_url.clone(),
-            reason: e.to_string(),
-        })?;
+        let profile_json = profile_response
+            .text()
+            .await
+            .map_err(|e| minecraft_api::download_error(&profile_url, e))?;
+
+        // Make sure the profile parses before it is saved as an installed version
+        minecraft_api::parse_version_json(&profile_json)?;
 
-        // Parse and save the profile
         let fabric_version_id = format!("fabric-loader-{}-{}", latest_loader, minecraft_version);
-        let versions_dir = format!("{}/versions/{}", base_dir, fabric_version_id);
-        let profile_path = format!("{}/{}.json", versions_dir, fabric_version_id);
+        let versions_dir = game_dir.version_dir(&fabric_version_id);
+        let profile_path = game_dir.version_json_path(&fabric_version_id);
 
         // Create directory
         std::fs::create_dir_all(&versions_dir).map_err(|e| LauncherError::FileSystemError {
             operation: "create_fabric_dir".to_string(),
-            path: versions_dir.clone(),
+            path: versions_dir.to_string_lossy().to_string(),
             error: e.to_string(),
         })?;
 
         // Save profile
         std::fs::write(&profile_path, &profile_json).map_err(|e| LauncherError::FileSystemError {
             operation: "write_fabric_profile".to_string(),
-            path: profile_path.clone(),
+            path: profile_path.to_string_lossy().to_string(),
             error: e.to_string(),
         })?;
 
         window.emit("log", "💾 Fabric profile saved successfully!").ok();
-
-        // Download Fabric libraries
-        let profile: serde_json::Value = serde_json::from_str(&profile_json).map_err(|e| LauncherError::DownloadFailed {
-            url: profile_url,
-            reason: format!("Failed to parse JSON: {}", e),
-        })?;
-
-        if let Some(libraries) = profile["libraries"].as_array() {
-            window.emit("log", "📚 Downloading Fabric libraries...").ok();
-            self.download_fabric_libraries(libraries, base_dir, window).await?;
-        }
-
-        Ok(())
-    }
-
-    /// Download Fabric libraries
-    async fn download_fabric_libraries(
-        &self,
-        libraries: &[serde_json::Value],
-        base_dir: &str,
-        window: &tauri::Window,
-    ) -> LauncherResult<()> {
-        for (i, lib) in libraries.iter().enumerate() {
-            if let Some(name) = lib["name"].as_str() {
-                window.emit("log", format!("📦 Downloading library {}/{}: {}", i + 1, libraries.len(), name)).ok();
-                
-                // Handle Fabric library format: name + url (not downloads.artifact)
-                if let Some(base_url) = lib["url"].as_str() {
-                    // Convert Maven coordinates to path: group:artifact:version -> group/artifact/version/artifact-version.jar
-                    let parts: Vec<&str> = name.split(':').collect();
-                    if parts.len() == 3 {
-                        let group = parts[0].replace('.', "/");
-                        let artifact = parts[1];
-                        let version = parts[2];
-                        
-                        let path = format!("{}/{}/{}/{}-{}.jar", group, artifact, version, artifact, version);
-                        let download_url = format!("{}{}", base_url, path);
-                        let lib_path = format!("{}/libraries/{}", base_dir, path);
-                        
-                        // Create directory for library
-                        if let Some(parent) = std::path::Path::new(&lib_path).parent() {
-                            std::fs::create_dir_all(parent).map_err(|e| LauncherError::FileSystemError {
-                                operation: "create_lib_dir".to_string(),
-                                path: parent.to_string_lossy().to_string(),
-                                error: e.to_string(),
-                            })?;
-                        }
-                        
-                        // Download library
-                        match self.downloader.download_file_with_progress(&download_url, &lib_path, Some(window)).await {
-                            Ok(_) => {
-                                window.emit("log", format!("✅ Downloaded: {}", path)).ok();
-                            }
-                            Err(e) => {
-                                window.emit("log", format!("❌ Failed to download {}: {}", path, e)).ok();
-                                return Err(e);
-                            }
-                        }
-                    }
-                }
-            }
-        }
         Ok(())
     }
 
@@ -454,233 +847,354 @@ impl MinecraftLauncher {
         &self,
         config: &LauncherConfig,
         minecraft_version: &str,
-        base_dir: &str,
+        game_dir: &GameDirectory,
         window: &tauri::Window,
     ) -> LauncherResult<()> {
         // First, download vanilla Minecraft resources
-        self.download_vanilla_resources(config, minecraft_version, base_dir, window).await?;
+        self.download_vanilla_resources(config, minecraft_version, game_dir, window).await?;
         
         // Then ensure Fabric is installed
-        self.ensure_fabric_installed(minecraft_version, base_dir, window).await?;
+        self.ensure_fabric_installed(minecraft_version, game_dir, window).await?;
         
         // Find the installed Fabric version dynamically
-        let versions_dir = format!("{}/versions", base_dir);
-        let fabric_version_id = std::fs::read_dir(&versions_dir)
-            .map_err(|e| LauncherError::FileSystemError {
-                operation: "read_versions_dir".to_string(),
-                path: versions_dir.clone(),
-                error: e.to_string(),
-            })?
-            .filter_map(|entry| entry.ok())
-            .filter_map(|entry| {
-                let name = entry.file_name().to_string_lossy().to_string();
-                if name.starts_with("fabric-loader-") && name.ends_with(&format!("-{}", minecraft_version)) {
-                    Some(name)
-                } else {
-                    None
-                }
-            })
-            .next()
+        let fabric_version_id = find_fabric_version(game_dir, minecraft_version)
             .ok_or_else(|| LauncherError::DownloadFailed {
                 url: "local".to_string(),
                 reason: "No Fabric installation found".to_string(),
             })?;
         
-        let fabric_profile_path = format!("{}/versions/{}/{}.json", base_dir, fabric_version_id, fabric_version_id);
-        
         window.emit("log", format!("Loading Fabric profile: {}", fabric_version_id)).ok();
-        
-        // Read Fabric profile
-        let profile_content = std::fs::read_to_string(&fabric_profile_path).map_err(|e| LauncherError::FileSystemError {
-            operation: "read_fabric_profile".to_string(),
-            path: fabric_profile_path.clone(),
-            error: e.to_string(),
-        })?;
-        
-        let fabric_profile: serde_json::Value = serde_json::from_str(&profile_content).map_err(|e| LauncherError::DownloadFailed {
-            url: fabric_profile_path,
-            reason: format!("Failed to parse Fabric profile: {}", e),
-        })?;
-        
-        // Build classpath with Fabric libraries
-        let mut classpath = Vec::new();
-        
-        // Add Fabric libraries first
-        if let Some(libraries) = fabric_profile["libraries"].as_array() {
-            window.emit("log", format!("🔗 Building classpath with {} Fabric libraries", libraries.len())).ok();
-            for lib in libraries {
-                if let Some(name) = lib["name"].as_str() {
-                    // Convert Maven coordinates to path: group:artifact:version -> group/artifact/version/artifact-version.jar
-                    let parts: Vec<&str> = name.split(':').collect();
-                    if parts.len() == 3 {
-                        let group = parts[0].replace('.', "/");
-                        let artifact = parts[1];
-                        let version = parts[2];
-                        
-                        let path = format!("{}/{}/{}/{}-{}.jar", group, artifact, version, artifact, version);
-                        let lib_path = format!("{}/libraries/{}", base_dir, path);
-                        
-                        if std::path::Path::new(&lib_path).exists() {
-                            classpath.push(lib_path.clone());
-                            window.emit("log", format!("✅ Added to classpath: {}", path)).ok();
-                        } else {
-                            window.emit("log", format!("❌ Missing library: {}", path)).ok();
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Add vanilla Minecraft libraries
-        let vanilla_version_path = format!("{}/versions/{}/{}.json", base_dir, minecraft_version, minecraft_version);
-        if let Ok(vanilla_content) = std::fs::read_to_string(&vanilla_version_path) {
-            if let Ok(vanilla_profile) = serde_json::from_str::<serde_json::Value>(&vanilla_content) {
-                if let Some(vanilla_libraries) = vanilla_profile["libraries"].as_array() {
-                    for lib in vanilla_libraries {
-                        if let Some(downloads) = lib["downloads"].as_object() {
-                            if let Some(artifact) = downloads["artifact"].as_object() {
-                                if let Some(path) = artifact["path"].as_str() {
-                                    let lib_path = format!("{}/libraries/{}", base_dir, path);
-                                    classpath.push(lib_path);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Add vanilla Minecraft jar
-        let minecraft_jar = format!("{}/versions/{}/{}.jar", base_dir, minecraft_version, minecraft_version);
-        classpath.push(minecraft_jar);
-        
-        let classpath_str = classpath.join(";");
-        
-        // Get main class from Fabric profile
-        let main_class = fabric_profile["mainClass"].as_str().unwrap_or("net.fabricmc.loader.impl.launch.knot.KnotClient");
-        
-        window.emit("log", format!("🎯 Main class: {}", main_class)).ok();
-        window.emit("log", format!("📚 Total classpath entries: {}", classpath.len())).ok();
-        
-        // Prepare Java command
-        let java_executable = config.get_java_executable();
-        let java_args = config.get_jvm_args();
-        
-        let mut command = Command::new(&java_executable);
-        
-        // Add JVM arguments
-        for arg in java_args {
-            command.arg(arg);
-        }
-
-        // Add Fabric-specific JVM arguments
-        if let Some(arguments) = fabric_profile["arguments"].as_object() {
-            if let Some(jvm_args) = arguments["jvm"].as_array() {
-                for arg in jvm_args {
-                    if let Some(arg_str) = arg.as_str() {
-                        command.arg(arg_str);
-                    }
+
+        // Merge the Fabric profile with the vanilla version it inherits from
+        let version_json = minecraft_api::resolve_version(game_dir, &fabric_version_id)?;
+
+        window.emit("log", format!("🎯 Main class: {}", version_json.main_class)).ok();
+
+        // Make sure every library of the merged profile is present, counting the loader's
+        // own libraries in the progress started for the vanilla files
+        let context = RuleContext::current();
+        let progress = self.downloader.progress();
+        for lib in version_json.libraries.iter().filter(|lib| lib.is_allowed(&context)) {
+            if let Some(path) = lib.artifact_path().map(|path| game_dir.library_path(&path)) {
+                if !path.exists() {
+                    progress.plan(&path, lib.artifact_size());
                 }
             }
         }
+        self.download_libraries(&version_json.libraries, game_dir, window).await?;
 
-        // Launch with Fabric
-        window.emit("log", format!("Launching Minecraft with Fabric using main class: {}", main_class)).ok();
-        
-        command
-            .arg("-cp")
-            .arg(&classpath_str)
-            .arg(main_class)
-            .arg("--username")
-            .arg(&config.player_name)
-            .arg("--version")
-            .arg(minecraft_version)
-            .arg("--gameDir")
-            .arg(base_dir)
-            .arg("--assetsDir")
-            .arg(format!("{}/assets", base_dir))
-            .arg("--assetIndex")
-            .arg(minecraft_version)
-            .arg("--uuid")
-            .arg(&config.player_uuid.clone().unwrap_or_default())
-            .arg("--accessToken")
-            .arg("N/A")
-            .arg("--userType")
-            .arg("legacy")
-            .arg("--versionType")
-            .arg("release");
-
-        let _output = command.current_dir(base_dir).spawn().map_err(|e| LauncherError::MinecraftLaunchError {
-            error: e.to_string(),
-        })?;
+        let jar_path = game_dir.version_jar_path(version_json.jar_id());
+        let natives_dir = game_dir.new_natives_dir(&version_json.id);
+        self.prepare_natives(&version_json.libraries, game_dir, &natives_dir, window).await?;
+        progress.complete();
 
-        Ok(())
+        // Launch with Fabric
+        window.emit("log", format!("Launching Minecraft with Fabric using main class: {}", version_json.main_class)).ok();
+        self.launch_minecraft(config, &version_json, &jar_path, &natives_dir, game_dir, window).await
     }
 
-    /// Download vanilla Minecraft resources without launching
+    /// Download vanilla Minecraft resources without launching. The download progress stays
+    /// open: callers complete it once natives and loader libraries are in place too.
     async fn download_vanilla_resources(
         &self,
         config: &LauncherConfig,
         version: &str,
-        base_dir: &str,
+        game_dir: &GameDirectory,
         window: &tauri::Window,
-    ) -> LauncherResult<()> {
-
-        // Download and parse version manifest
-        window.emit("log", "Downloading version manifest...").ok();
-        let manifest_json = minecraft_api::download_version_manifest(self.downloader.client()).await?;
-        let manifest = minecraft_api::parse_version_manifest(&manifest_json)?;
-
-        // Find the selected version
-        let selected_version = manifest
-            .versions
-            .iter()
-            .find(|v| v.id == version)
-            .ok_or_else(|| LauncherError::VersionNotFound { version: version.to_string() })?;
+    ) -> LauncherResult<minecraft_api::VersionJson> {
+        let version_json = self.load_version_json(config, version, game_dir, window).await?;
+        if version_json.is_legacy() {
+            window.emit("log", "Legacy version format detected, using minecraftArguments").ok();
+        }
 
-        // Download version JSON
-        window
-            .emit("log", format!("Downloading version JSON: {}", selected_version.id))
-            .ok();
-        let version_json_str =
-            minecraft_api::download_version_json(self.downloader.client(), &selected_version.url).await?;
-        let version_json = minecraft_api::parse_version_json(&version_json_str)?;
+        // Plan every download up front so `download_progress` has a stable total
+        let files = self.collect_game_files(&version_json, game_dir, false, window).await?;
+        let progress = self.downloader.progress();
+        progress.begin(window);
+        for file in files.iter().filter(|file| !file.is_present()) {
+            progress.plan(&file.path, file.size);
+        }
 
         // Download main JAR
         window.emit("log", "Downloading main JAR...").ok();
-        let jar_path = format!(
-            "{}/versions/{}/{}.jar",
-            base_dir, selected_version.id, selected_version.id
-        );
-        
-        // Create version directory
-        let version_dir = format!("{}/versions/{}", base_dir, selected_version.id);
-        std::fs::create_dir_all(&version_dir).map_err(|e| LauncherError::FileSystemError {
-            operation: "create_version_dir".to_string(),
-            path: version_dir,
-            error: e.to_string(),
-        })?;
-        
-        // Save version JSON
-        let version_json_path = format!("{}/versions/{}/{}.json", base_dir, selected_version.id, selected_version.id);
-        std::fs::write(&version_json_path, &version_json_str).map_err(|e| LauncherError::FileSystemError {
-            operation: "save_version_json".to_string(),
-            path: version_json_path,
-            error: e.to_string(),
-        })?;
-        
-        self.downloader
-            .download_file_with_progress(&version_json.downloads.client.url, &jar_path, Some(window))
-            .await?;
+        let jar_path = game_dir.version_jar_path(version_json.jar_id());
+        self.download_client_jar(&version_json, &jar_path, window).await?;
 
         // Download libraries
         window.emit("log", "Downloading libraries...").ok();
-        self.download_libraries(&version_json.libraries, base_dir, window).await?;
+        self.download_libraries(&version_json.libraries, game_dir, window).await?;
 
         // Download assets
         window.emit("log", "Downloading assets...").ok();
-        self.download_assets(&version_json.asset_index, base_dir, window).await?;
+        match &version_json.asset_index {
+            Some(asset_index) => self.download_assets(asset_index, game_dir, window).await?,
+            None => {
+                window.emit("log", "No asset index in version JSON, skipping assets").ok();
+            }
+        }
 
-        Ok(())
+        Ok(version_json)
+    }
+}
+
+/// Id of an installed Fabric profile for `minecraft_version`, if any
+fn find_fabric_version(game_dir: &GameDirectory, minecraft_version: &str) -> Option<String> {
+    std::fs::read_dir(game_dir.versions_dir())
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .find(|name| name.starts_with("fabric-loader-") && name.ends_with(&format!("-{}", minecraft_version)))
+}
+
+/// Files a launch needs that aren't on disk: classpath entries (including the client jar),
+/// native jars for this OS and the asset index
+fn missing_launch_files(version_json: &minecraft_api::VersionJson, game_dir: &GameDirectory) -> Vec<String> {
+    let context = RuleContext::current();
+    let jar_path = game_dir.version_jar_path(version_json.jar_id());
+
+    let mut required = paths::build_classpath(&version_json.libraries, game_dir, &jar_path, &context);
+    for lib in version_json.libraries.iter().filter(|lib| lib.is_allowed(&context)) {
+        if let Some(path) = lib.native_download(&context).and_then(|native| native.path.as_ref()) {
+            required.push(game_dir.library_path(path));
+        }
+    }
+    if version_json.asset_index.is_some() {
+        required.push(game_dir.asset_index_path(version_json.asset_index_id()));
+    }
+
+    required
+        .into_iter()
+        .filter(|path| !path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Extract every native jar for this OS into `natives_dir`, a directory of this launch only
+fn extract_all_natives(
+    libraries: &[minecraft_api::Library],
+    game_dir: &GameDirectory,
+    natives_dir: &Path,
+    window: &tauri::Window,
+) -> LauncherResult<()> {
+    let context = RuleContext::current();
+
+    remove_stale_natives(natives_dir);
+    std::fs::create_dir_all(natives_dir).map_err(|e| LauncherError::FileSystemError {
+        operation: "create_natives_dir".to_string(),
+        path: natives_dir.to_string_lossy().to_string(),
+        error: e.to_string(),
+    })?;
+
+    for lib in libraries.iter().filter(|lib| lib.is_allowed(&context)) {
+        if let Some(native_path_rel) = lib.native_download(&context).and_then(|native| native.path.as_ref()) {
+            let native_path = game_dir.library_path(native_path_rel);
+            let exclude = lib.extract.as_ref().map(|e| e.exclude.as_slice()).unwrap_or_default();
+            extract_natives(&native_path, natives_dir, exclude)?;
+            window.emit("log", format!("Extracted natives: {}", lib.name)).ok();
+        }
+    }
+    Ok(())
+}
+
+/// JSON files of `id` and the versions it inherits from that are already installed, child first
+fn installed_version_chain(game_dir: &GameDirectory, id: &str) -> Vec<PathBuf> {
+    let mut chain = Vec::new();
+    let mut next = Some(id.to_string());
+    while let Some(id) = next.take() {
+        let path = game_dir.version_json_path(&id);
+        if chain.contains(&path) {
+            break;
+        }
+        let Ok(version) = minecraft_api::read_local_version_json(game_dir, &id) else {
+            break;
+        };
+        chain.push(path);
+        next = version.inherits_from;
+    }
+    chain
+}
+
+/// The natives belong to the game process from now on: record it for later launches, and
+/// remove them once it exits. If the launcher quits first, a later launch cleans them up.
+fn release_natives_on_exit(mut child: Child, natives_dir: &Path) {
+    let _ = std::fs::write(natives_dir.join(NATIVES_PID_FILE), child.id().to_string());
+    let natives_dir = natives_dir.to_path_buf();
+    std::thread::spawn(move || {
+        let _ = child.wait();
+        let _ = std::fs::remove_dir_all(&natives_dir);
+    });
+}
+
+/// Best-effort removal of the natives directories other launches left next to `keep`
+/// whose process is gone: the game recorded in `NATIVES_PID_FILE`, or before the game
+/// started, the launcher whose pid is in the directory name. The fixed `natives` directory
+/// older versions used has no owner and always goes.
+fn remove_stale_natives(keep: &Path) {
+    let Some(entries) = keep.parent().and_then(|version_dir| std::fs::read_dir(version_dir).ok()) else {
+        return;
+    };
+
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, false);
+    let running = |pid: u32| system.process(Pid::from_u32(pid)).is_some();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == keep || !entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let stale = if name == "natives" {
+            true
+        } else if let Some(owner) = name.strip_prefix(paths::NATIVES_DIR_PREFIX) {
+            let game = std::fs::read_to_string(path.join(NATIVES_PID_FILE))
+                .ok()
+                .and_then(|pid| pid.trim().parse().ok());
+            let launcher = owner.split('-').next().and_then(|pid| pid.parse().ok());
+            match (game, launcher) {
+                (Some(game), _) => !running(game),
+                (None, Some(launcher)) => !running(launcher),
+                (None, None) => false,
+            }
+        } else {
+            false
+        };
+        if stale {
+            let _ = std::fs::remove_dir_all(&path);
+        }
+    }
+}
+
+/// Add the SHA-1 of every file the installed versions of `game_dir` use to `hashes`
+fn collect_referenced_hashes(game_dir: &GameDirectory, hashes: &mut HashSet<String>) {
+    let mut add = |sha1: Option<&str>| {
+        if let Some(sha1) = sha1 {
+            hashes.insert(sha1.to_ascii_lowercase());
+        }
+    };
+
+    for version in minecraft_api::scan_local_versions(game_dir) {
+        let Ok(version_json) = minecraft_api::resolve_version(game_dir, &version.id) else {
+            continue;
+        };
+
+        if let Some(downloads) = &version_json.downloads {
+            add(downloads.client.sha1.as_deref());
+        }
+        for lib in &version_json.libraries {
+            add(lib.artifact_sha1());
+            // Natives for every platform, not just this one
+            let classifiers = lib.downloads.as_ref().and_then(|d| d.classifiers.as_ref());
+            for native in classifiers.into_iter().flat_map(|c| c.values()) {
+                add(native.sha1.as_deref());
+            }
+        }
+        if let Some(asset_index) = &version_json.asset_index {
+            add(asset_index.sha1.as_deref());
+        }
+        if let Ok(index) = minecraft_api::read_asset_index(game_dir, version_json.asset_index_id()) {
+            for object in index.objects.values() {
+                add(Some(&object.hash));
+            }
+        }
+    }
+}
+
+/// Extract a natives jar into `natives_dir`, skipping entries matched by the `extract.exclude` prefixes
+fn extract_natives(jar_path: &Path, natives_dir: &Path, exclude: &[String]) -> LauncherResult<()> {
+    let file = File::open(jar_path).map_err(|e| LauncherError::FileSystemError {
+        operation: "open_natives_jar".to_string(),
+        path: jar_path.to_string_lossy().to_string(),
+        error: e.to_string(),
+    })?;
+
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| LauncherError::FileSystemError {
+        operation: "read_natives_jar".to_string(),
+        path: jar_path.to_string_lossy().to_string(),
+        error: e.to_string(),
+    })?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| LauncherError::FileSystemError {
+            operation: "read_natives_jar".to_string(),
+            path: jar_path.to_string_lossy().to_string(),
+            error: e.to_string(),
+        })?;
+
+        if entry.is_dir() || exclude.iter().any(|prefix| entry.name().starts_with(prefix.as_str())) {
+            continue;
+        }
+
+        // Ignore entries that would escape the natives directory
+        let Some(relative_path) = entry.enclosed_name() else {
+            continue;
+        };
+        let out_path = natives_dir.join(relative_path);
+
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out_file = File::create(&out_path)?;
+        std::io::copy(&mut entry, &mut out_file)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natives_are_removed_once_their_process_is_gone() {
+        let dir = tempfile::tempdir().unwrap();
+        let game_dir = GameDirectory::new(dir.path());
+        let version_dir = game_dir.version_dir("1.20.1");
+        // Far above any real pid limit, so never running
+        let dead = 4_000_000_000u32;
+        let alive = std::process::id();
+
+        let current = game_dir.new_natives_dir("1.20.1");
+        let launching = version_dir.join(format!("natives-{}-1", alive));
+        let launcher_gone = version_dir.join(format!("natives-{}-2", dead));
+        let game_running = version_dir.join(format!("natives-{}-3", dead));
+        let game_exited = version_dir.join(format!("natives-{}-4", alive));
+        for dir in [&current, &launching, &launcher_gone, &game_running, &game_exited, &version_dir.join("natives"), &version_dir.join("mods")] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(game_running.join(NATIVES_PID_FILE), alive.to_string()).unwrap();
+        std::fs::write(game_exited.join(NATIVES_PID_FILE), dead.to_string()).unwrap();
+
+        remove_stale_natives(&current);
+
+        assert!(current.exists());
+        assert!(launching.exists());
+        assert!(game_running.exists());
+        assert!(version_dir.join("mods").exists());
+        assert!(!launcher_gone.exists());
+        assert!(!game_exited.exists());
+        assert!(!version_dir.join("natives").exists());
+    }
+
+    #[test]
+    fn installed_version_chain_follows_inherits_from() {
+        let dir = tempfile::tempdir().unwrap();
+        let game_dir = GameDirectory::new(dir.path());
+        for (id, json) in [
+            ("fabric-loader-0.16.9-1.20.1", r#"{"id": "fabric-loader-0.16.9-1.20.1", "inheritsFrom": "1.20.1"}"#),
+            ("1.20.1", r#"{"id": "1.20.1"}"#),
+        ] {
+            std::fs::create_dir_all(game_dir.version_dir(id)).unwrap();
+            std::fs::write(game_dir.version_json_path(id), json).unwrap();
+        }
+
+        assert_eq!(
+            installed_version_chain(&game_dir, "fabric-loader-0.16.9-1.20.1"),
+            vec![
+                game_dir.version_json_path("fabric-loader-0.16.9-1.20.1"),
+                game_dir.version_json_path("1.20.1"),
+            ]
+        );
+        assert!(installed_version_chain(&game_dir, "1.21").is_empty());
     }
 }
@@ -4,13 +4,24 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+pub mod arguments;
+pub mod cache;
 pub mod commands;
 pub mod config;
 pub mod downloader;
+pub mod endpoints;
 pub mod errors;
+pub mod http;
+pub mod java;
 pub mod launcher;
 pub mod minecraft_api;
 pub mod mods;
+pub mod paths;
+pub mod progress;
+pub mod runtime;
+pub mod store;
+#[cfg(test)]
+mod testing;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
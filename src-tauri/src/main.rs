@@ -1,13 +1,24 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod arguments;
+mod cache;
 mod commands;
 mod config;
 mod downloader;
+mod endpoints;
 mod errors;
+mod http;
+mod java;
 mod launcher;
 mod minecraft_api;
 mod mods;
+mod paths;
+mod progress;
+mod runtime;
+mod store;
+#[cfg(test)]
+mod testing;
 
 use commands::*;
 
@@ -17,10 +28,13 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             start_launcher,
             get_versions,
+            verify_game_files,
+            gc_shared_store,
             get_config,
             update_config,
             validate_config,
             get_system_info,
+            get_java_installations,
             get_installed_mods,
             toggle_mod,
             delete_mod,
@@ -1,9 +1,16 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::cache::MetadataCache;
+use crate::downloader::Downloader;
+use crate::endpoints::Endpoint;
+use crate::paths::GameDirectory;
+use crate::progress::ProgressTracker;
 use tauri::Emitter;
 use thiserror::Error;
 
@@ -27,6 +34,19 @@ pub enum LauncherError {
     #[error("Configuration validation error in {field}: {message}")]
     ConfigValidation { field: String, message: String },
 
+    #[error("Download failed for {url}: {reason}")]
+    DownloadFailed { url: String, reason: String },
+
+    #[error("{} asset(s) failed to download: {}", .failed.len(), .failed.join(", "))]
+    AssetDownloadFailed { failed: Vec<String> },
+
+    #[error("File hash mismatch for {file}: expected {expected}, got {actual}")]
+    HashMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+
     #[error("File system error: {operation} - {path}: {error}")]
     FileSystemError {
         operation: String,
@@ -42,39 +62,426 @@ pub struct VersionManifest {
     pub versions: Vec<MinecraftVersion>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct MinecraftVersion {
     pub id: String,
     pub r#type: String,
+    /// Version JSON URL; empty for versions that only exist locally
+    #[serde(default)]
     pub url: String,
+    /// SHA-1 of the version JSON (v2 manifest only)
+    pub sha1: Option<String>,
+    pub time: Option<String>,
+    pub release_time: Option<String>,
+    /// `1` for versions with the player safety features (chat reporting), `0` before them
+    pub compliance_level: Option<u32>,
+    #[serde(default)]
+    pub origin: VersionOrigin,
+}
+
+/// Where a version listed by the launcher comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionOrigin {
+    /// Only in the remote manifest
+    #[default]
+    Remote,
+    /// Only under `versions/` (custom profiles, loader installs, removed versions)
+    Local,
+    /// In the remote manifest and already installed under `versions/`
+    Both,
+}
+
+/// Just enough of a version JSON to list it
+#[derive(Debug, Deserialize)]
+struct LocalVersionHeader {
+    id: String,
+    r#type: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VersionJson {
+    pub id: String,
+    pub r#type: Option<String>,
+    /// Parent version this one extends (loader and custom profiles)
+    pub inherits_from: Option<String>,
+    /// Version whose client jar is launched, when it differs from `id`
+    pub jar: Option<String>,
+    #[serde(default)]
     pub main_class: String,
     pub arguments: Option<Arguments>,
+    /// Space separated game arguments used by versions before 1.13
+    pub minecraft_arguments: Option<String>,
+    #[serde(default)]
     pub libraries: Vec<Library>,
-    pub downloads: Downloads,
-    pub asset_index: AssetIndex,
+    /// Missing from very old and hand-written version JSONs
+    pub downloads: Option<Downloads>,
+    pub asset_index: Option<AssetIndex>,
+    /// Asset index id used by versions that predate `assetIndex`
+    pub assets: Option<String>,
+    pub java_version: Option<JavaVersion>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaVersion {
+    pub component: Option<String>,
+    pub major_version: u32,
+}
+
+impl VersionJson {
+    /// Whether this version uses the pre-1.13 `minecraftArguments` format, including loader
+    /// profiles that add an `arguments` block on top of such a version
+    pub fn is_legacy(&self) -> bool {
+        self.arguments.is_none() || self.minecraft_arguments.is_some()
+    }
+
+    /// Id of the asset index this version uses
+    pub fn asset_index_id(&self) -> &str {
+        self.asset_index
+            .as_ref()
+            .map(|index| index.id.as_str())
+            .or(self.assets.as_deref())
+            .unwrap_or("legacy")
+    }
+
+    /// Id of the version whose client jar should be launched
+    pub fn jar_id(&self) -> &str {
+        self.jar.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Merge this version on top of its parent following the vanilla launcher semantics:
+    /// scalar fields fall back to the parent, libraries are prepended and arguments appended.
+    pub fn merge_parent(mut self, parent: VersionJson) -> VersionJson {
+        // Loader profiles usually ship no client jar of their own
+        if self.jar.is_none() && self.downloads.is_none() {
+            self.jar = Some(parent.jar_id().to_string());
+        }
+
+        if self.main_class.is_empty() {
+            self.main_class = parent.main_class;
+        }
+        self.r#type = self.r#type.or(parent.r#type);
+        self.minecraft_arguments = self.minecraft_arguments.or(parent.minecraft_arguments);
+        self.downloads = self.downloads.or(parent.downloads);
+        self.asset_index = self.asset_index.or(parent.asset_index);
+        self.assets = self.assets.or(parent.assets);
+        self.java_version = self.java_version.or(parent.java_version);
+        self.inherits_from = parent.inherits_from;
+
+        self.libraries.extend(parent.libraries);
+
+        self.arguments = match (parent.arguments, self.arguments) {
+            (Some(parent_args), Some(child_args)) => Some(Arguments {
+                game: concat_arguments(parent_args.game, child_args.game),
+                jvm: concat_arguments(parent_args.jvm, child_args.jvm),
+            }),
+            (parent_args, child_args) => child_args.or(parent_args),
+        };
+
+        self
+    }
+}
+
+fn concat_arguments(
+    parent: Option<Vec<Argument>>,
+    child: Option<Vec<Argument>>,
+) -> Option<Vec<Argument>> {
+    match (parent, child) {
+        (Some(mut parent), Some(child)) => {
+            parent.extend(child);
+            Some(parent)
+        }
+        (parent, child) => child.or(parent),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Arguments {
-    pub game: Option<Vec<Value>>,
-    pub jvm: Option<Vec<Value>>,
+    pub game: Option<Vec<Argument>>,
+    pub jvm: Option<Vec<Argument>>,
+}
+
+/// A single entry of `arguments.game` / `arguments.jvm`: either a plain string
+/// or a value guarded by a list of rules.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Argument {
+    Plain(String),
+    Conditional { rules: Vec<Rule>, value: ArgumentValue },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ArgumentValue {
+    Single(String),
+    Many(Vec<String>),
+}
+
+impl Argument {
+    /// Returns the raw (unsubstituted) values this argument contributes for the given context
+    pub fn values(&self, context: &RuleContext) -> Vec<&str> {
+        match self {
+            Argument::Plain(value) => vec![value.as_str()],
+            Argument::Conditional { rules, value } => {
+                if !rules_allow(rules, context) {
+                    return Vec::new();
+                }
+                match value {
+                    ArgumentValue::Single(value) => vec![value.as_str()],
+                    ArgumentValue::Many(values) => values.iter().map(String::as_str).collect(),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Disallow,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Rule {
+    pub action: RuleAction,
+    pub os: Option<OsRule>,
+    pub features: Option<HashMap<String, bool>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct OsRule {
+    pub name: Option<String>,
+    pub arch: Option<String>,
+    /// Regular expression matched against the OS version
+    pub version: Option<String>,
+}
+
+/// Environment that rules are evaluated against
+#[derive(Debug, Clone, Default)]
+pub struct RuleContext {
+    pub os_name: String,
+    pub os_arch: String,
+    pub os_version: Option<String>,
+    pub features: HashMap<String, bool>,
+}
+
+impl RuleContext {
+    /// Context describing the host the launcher is running on, with every feature disabled
+    pub fn current() -> Self {
+        Self {
+            os_name: current_os_name().to_string(),
+            os_arch: current_os_arch().to_string(),
+            os_version: sysinfo::System::os_version(),
+            features: HashMap::new(),
+        }
+    }
+
+    pub fn with_feature(mut self, name: &str, enabled: bool) -> Self {
+        self.features.insert(name.to_string(), enabled);
+        self
+    }
+}
+
+/// OS name as used by the version JSON (`windows`, `osx`, `linux`)
+pub fn current_os_name() -> &'static str {
+    match std::env::consts::OS {
+        "windows" => "windows",
+        "macos" => "osx",
+        _ => "linux",
+    }
+}
+
+/// OS architecture as used by the version JSON
+pub fn current_os_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86" => "x86",
+        "x86_64" => "x86_64",
+        "aarch64" => "arm64",
+        "arm" => "arm32",
+        other => other,
+    }
+}
+
+impl OsRule {
+    fn matches(&self, context: &RuleContext) -> bool {
+        if let Some(name) = &self.name {
+            if name != &context.os_name {
+                return false;
+            }
+        }
+
+        if let Some(arch) = &self.arch {
+            if arch != &context.os_arch {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.version {
+            let version = context.os_version.as_deref().unwrap_or_default();
+            match regex::Regex::new(pattern) {
+                Ok(re) if re.is_match(version) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+impl Rule {
+    /// Whether every condition of this rule holds for the given context
+    pub fn matches(&self, context: &RuleContext) -> bool {
+        if let Some(os) = &self.os {
+            if !os.matches(context) {
+                return false;
+            }
+        }
+
+        if let Some(features) = &self.features {
+            for (name, expected) in features {
+                let actual = context.features.get(name).copied().unwrap_or(false);
+                if actual != *expected {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Evaluate a rule list the way the vanilla launcher does: an empty list allows,
+/// otherwise the last matching rule decides and nothing matching means disallow.
+pub fn rules_allow(rules: &[Rule], context: &RuleContext) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+
+    let mut allowed = false;
+    for rule in rules {
+        if rule.matches(context) {
+            allowed = rule.action == RuleAction::Allow;
+        }
+    }
+    allowed
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Library {
     pub name: String,
     pub downloads: Option<LibraryDownloads>,
+    /// Maven repository for libraries that only give a `name` (older and loader profiles)
+    pub url: Option<String>,
+    pub rules: Option<Vec<Rule>>,
+    /// OS name -> classifier holding the native libraries for that OS
+    pub natives: Option<HashMap<String, String>>,
+    pub extract: Option<ExtractRules>,
+}
+
+/// Repository used for libraries that specify neither `downloads` nor `url`
+const DEFAULT_LIBRARY_REPOSITORY: &str = "https://libraries.minecraft.net/";
+
+/// Convert Maven coordinates (`group:artifact:version[:classifier][@ext]`) to a repository path
+pub fn maven_path(name: &str) -> Option<String> {
+    let (coordinates, extension) = name.split_once('@').unwrap_or((name, "jar"));
+    let parts: Vec<&str> = coordinates.split(':').collect();
+
+    let (group, artifact, version, classifier) = match parts.as_slice() {
+        [group, artifact, version] => (group, artifact, version, None),
+        [group, artifact, version, classifier] => (group, artifact, version, Some(classifier)),
+        _ => return None,
+    };
+
+    let file_name = match classifier {
+        Some(classifier) => format!("{}-{}-{}.{}", artifact, version, classifier, extension),
+        None => format!("{}-{}.{}", artifact, version, extension),
+    };
+
+    Some(format!(
+        "{}/{}/{}/{}",
+        group.replace('.', "/"),
+        artifact,
+        version,
+        file_name
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtractRules {
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl Library {
+    /// Whether this library applies to the given context
+    pub fn is_allowed(&self, context: &RuleContext) -> bool {
+        self.rules
+            .as_deref()
+            .is_none_or(|rules| rules_allow(rules, context))
+    }
+
+    /// Path of the main artifact relative to `libraries/`
+    pub fn artifact_path(&self) -> Option<String> {
+        match self.downloads.as_ref().and_then(|d| d.artifact.as_ref()) {
+            Some(artifact) => artifact.path.clone().or_else(|| maven_path(&self.name)),
+            // Natives-only entries have no main artifact
+            None if self.natives.is_some() => None,
+            None => maven_path(&self.name),
+        }
+    }
+
+    /// Download URL of the main artifact
+    pub fn artifact_url(&self) -> Option<String> {
+        if let Some(artifact) = self.downloads.as_ref().and_then(|d| d.artifact.as_ref()) {
+            return Some(artifact.url.clone());
+        }
+
+        let repository = self.url.as_deref().unwrap_or(DEFAULT_LIBRARY_REPOSITORY);
+        let path = self.artifact_path()?;
+        Some(format!("{}/{}", repository.trim_end_matches('/'), path))
+    }
+
+    /// Expected SHA-1 of the main artifact, when the version JSON provides one
+    pub fn artifact_sha1(&self) -> Option<&str> {
+        self.downloads
+            .as_ref()?
+            .artifact
+            .as_ref()?
+            .sha1
+            .as_deref()
+    }
+
+    /// Expected size in bytes of the main artifact, when the version JSON provides one
+    pub fn artifact_size(&self) -> Option<u64> {
+        self.downloads.as_ref()?.artifact.as_ref()?.size
+    }
+
+    /// Natives classifier for the given context, with `${arch}` substituted
+    pub fn native_classifier(&self, context: &RuleContext) -> Option<String> {
+        let classifier = self.natives.as_ref()?.get(&context.os_name)?;
+        let bits = if context.os_arch.contains("64") { "64" } else { "32" };
+        Some(classifier.replace("${arch}", bits))
+    }
+
+    /// Download of the natives jar for the given context, if this library ships one
+    pub fn native_download(&self, context: &RuleContext) -> Option<&DownloadInfo> {
+        let classifier = self.native_classifier(context)?;
+        self.downloads
+            .as_ref()?
+            .classifiers
+            .as_ref()?
+            .get(&classifier)
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct LibraryDownloads {
     pub artifact: Option<DownloadInfo>,
-    pub classifiers: Option<Value>,
+    pub classifiers: Option<HashMap<String, DownloadInfo>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -82,6 +489,7 @@ pub struct DownloadInfo {
     pub url: String,
     pub path: Option<String>,
     pub sha1: Option<String>,
+    pub size: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -90,15 +498,19 @@ pub struct Downloads {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AssetIndex {
     pub id: String,
     pub url: String,
+    pub sha1: Option<String>,
+    pub size: Option<u64>,
+    /// Combined size of every object the index references
+    pub total_size: Option<u64>,
 }
-pub async fn download_version_manifest(client: &Client) -> Result<String> {
-    let url = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
-    let resp = client.get(url).send().await?;
-    let text = resp.text().await?;
-    Ok(text)
+
+pub async fn download_version_manifest(client: &Client, cache: &MetadataCache) -> Result<String> {
+    let url = Endpoint::MojangMeta.url("mc/game/version_manifest_v2.json");
+    cache.fetch(client, &url).await
 }
 
 pub fn parse_version_manifest(json: &str) -> Result<VersionManifest> {
@@ -106,81 +518,845 @@ pub fn parse_version_manifest(json: &str) -> Result<VersionManifest> {
     Ok(manifest)
 }
 
-pub async fn download_version_json(client: &Client, url: &str) -> Result<String> {
-    let resp = client.get(url).send().await?;
-    let text = resp.text().await?;
+/// Download a version JSON and check it against the SHA-1 from the manifest.
+/// A cached copy that fails the check is dropped and fetched once more.
+pub async fn download_version_json(
+    client: &Client,
+    cache: &MetadataCache,
+    url: &str,
+    expected_sha1: Option<&str>,
+) -> Result<String> {
+    let text = cache.fetch(client, url).await?;
+    let Some(expected) = expected_sha1 else {
+        return Ok(text);
+    };
+
+    if sha1_hex(text.as_bytes()) == expected {
+        return Ok(text);
+    }
+
+    cache.invalidate(url);
+    let text = cache.fetch(client, url).await?;
+    let actual = sha1_hex(text.as_bytes());
+    if actual != expected {
+        cache.invalidate(url);
+        return Err(LauncherError::HashMismatch {
+            file: url.to_string(),
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+
     Ok(text)
 }
 
+/// Lowercase hex SHA-1 of `bytes`
+pub fn sha1_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha1::digest(bytes))
+}
+
 pub fn parse_version_json(json: &str) -> Result<VersionJson> {
     let version_json: VersionJson = serde_json::from_str(json)?;
     Ok(version_json)
 }
 
-pub async fn download_file(client: &Client, url: &str, path: &str) -> Result<()> {
-    let resp = client.get(url).send().await?;
-    let bytes = resp.bytes().await?;
-    let parent = Path::new(path).parent().unwrap();
-    if !parent.exists() {
+/// Read `versions/<id>/<id>.json` without resolving `inheritsFrom`
+pub fn read_local_version_json(game_dir: &GameDirectory, id: &str) -> Result<VersionJson> {
+    let path = game_dir.version_json_path(id);
+    if !path.exists() {
+        return Err(LauncherError::VersionNotFound {
+            version: id.to_string(),
+        });
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| LauncherError::FileSystemError {
+        operation: "read_version_json".to_string(),
+        path: path.to_string_lossy().to_string(),
+        error: e.to_string(),
+    })?;
+    parse_version_json(&content)
+}
+
+/// List versions installed under `versions/<id>/<id>.json`
+pub fn scan_local_versions(game_dir: &GameDirectory) -> Vec<MinecraftVersion> {
+    let Ok(entries) = fs::read_dir(game_dir.versions_dir()) else {
+        return Vec::new();
+    };
+
+    let mut versions: Vec<MinecraftVersion> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let id = entry.file_name().to_string_lossy().to_string();
+            let content = fs::read_to_string(game_dir.version_json_path(&id)).ok()?;
+            let header: LocalVersionHeader = serde_json::from_str(&content).ok()?;
+            // The directory name is what everything else is keyed on
+            if header.id != id {
+                return None;
+            }
+            Some(MinecraftVersion {
+                id,
+                r#type: header.r#type.unwrap_or_else(|| "custom".to_string()),
+                url: String::new(),
+                sha1: None,
+                time: None,
+                release_time: None,
+                compliance_level: None,
+                origin: VersionOrigin::Local,
+            })
+        })
+        .collect();
+
+    versions.sort_by(|a, b| a.id.cmp(&b.id));
+    versions
+}
+
+/// Combine the remote manifest with locally installed versions, keeping the manifest order first
+pub fn merge_versions(
+    remote: Vec<MinecraftVersion>,
+    local: Vec<MinecraftVersion>,
+) -> Vec<MinecraftVersion> {
+    let mut merged = remote;
+
+    for local_version in local {
+        match merged.iter_mut().find(|v| v.id == local_version.id) {
+            Some(existing) => existing.origin = VersionOrigin::Both,
+            None => merged.push(local_version),
+        }
+    }
+
+    merged
+}
+
+/// Load a locally installed version and merge its whole `inheritsFrom` chain into one effective version
+pub fn resolve_version(game_dir: &GameDirectory, id: &str) -> Result<VersionJson> {
+    let mut version = read_local_version_json(game_dir, id)?;
+    let mut visited = vec![version.id.clone()];
+
+    while let Some(parent_id) = version.inherits_from.clone() {
+        if visited.contains(&parent_id) {
+            return Err(LauncherError::FileSystemError {
+                operation: "resolve_inherits_from".to_string(),
+                path: game_dir.version_json_path(id).to_string_lossy().to_string(),
+                error: format!("circular inheritsFrom chain: {} -> {}", visited.join(" -> "), parent_id),
+            });
+        }
+
+        let parent = read_local_version_json(game_dir, &parent_id)?;
+        visited.push(parent_id);
+        version = version.merge_parent(parent);
+    }
+
+    Ok(version)
+}
+
+/// Contents of `assets/indexes/<id>.json`
+#[derive(Debug, Deserialize)]
+pub struct AssetIndexFile {
+    pub objects: HashMap<String, AssetObject>,
+    /// Pre-1.7 indexes: objects are also copied by name into `assets/virtual/<id>`
+    #[serde(default, rename = "virtual")]
+    pub is_virtual: bool,
+    /// Pre-1.6 indexes: objects are also copied by name into `<gameDir>/resources`
+    #[serde(default)]
+    pub map_to_resources: bool,
+}
+
+impl AssetIndexFile {
+    /// Directory the game reads assets from by name (`${game_assets}`), if this index uses a
+    /// legacy layout. Modern indexes return `None` and the game uses the hashed object store.
+    pub fn legacy_assets_dir(&self, game_dir: &GameDirectory, index_id: &str) -> Option<PathBuf> {
+        if self.map_to_resources {
+            Some(game_dir.resources_dir())
+        } else if self.is_virtual {
+            Some(game_dir.virtual_assets_dir(index_id))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetObject {
+    pub hash: String,
+    pub size: u64,
+}
+
+impl AssetObject {
+    /// Cheap presence check: the file exists with the expected size. Truncated or
+    /// zero-byte objects fail this; full rehashing is left to game file verification.
+    pub fn is_present(&self, path: &Path) -> bool {
+        fs::metadata(path).map(|meta| meta.len() == self.size).unwrap_or(false)
+    }
+}
+
+pub fn parse_asset_index(json: &str) -> Result<AssetIndexFile> {
+    let index: AssetIndexFile = serde_json::from_str(json)?;
+    Ok(index)
+}
+
+pub fn read_asset_index(game_dir: &GameDirectory, index_id: &str) -> Result<AssetIndexFile> {
+    let index_str = fs::read_to_string(game_dir.asset_index_path(index_id))?;
+    parse_asset_index(&index_str)
+}
+
+/// Copy hashed objects by name into the virtual/resources tree of a legacy index.
+/// Returns the directory to pass as `${game_assets}`, or `None` for modern indexes.
+pub fn materialize_legacy_assets(
+    index: &AssetIndexFile,
+    game_dir: &GameDirectory,
+    index_id: &str,
+) -> Result<Option<PathBuf>> {
+    let Some(target_dir) = index.legacy_assets_dir(game_dir, index_id) else {
+        return Ok(None);
+    };
+
+    for (name, object) in &index.objects {
+        let target = crate::paths::join_relative(&target_dir, name);
+        if object.is_present(&target) {
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(game_dir.asset_object_path(&object.hash), &target)?;
+    }
+
+    Ok(Some(target_dir))
+}
+
+/// Keep connection failures as `Network` so callers can tell "offline" apart from a bad
+/// response, and describe everything else as a failed download of `url`
+pub fn download_error(url: &str, error: reqwest::Error) -> LauncherError {
+    if error.is_connect() || error.is_timeout() {
+        LauncherError::Network(error)
+    } else {
+        LauncherError::DownloadFailed {
+            url: url.to_string(),
+            reason: error.to_string(),
+        }
+    }
+}
+
+/// Download `url` to `path`, checking the SHA-1 and size on the fly
+pub async fn download_file_checked(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    expected_sha1: Option<&str>,
+    expected_size: Option<u64>,
+    progress: Option<&ProgressTracker>,
+) -> Result<()> {
+    let Some(progress) = progress else {
+        return download_resumable(client, url, path, expected_sha1, expected_size, |_| {}).await;
+    };
+
+    progress.start_file(path, expected_size);
+    let result =
+        download_resumable(client, url, path, expected_sha1, expected_size, |n| progress.advance(path, n)).await;
+    match &result {
+        Ok(()) => progress.finish_file(path),
+        Err(_) => progress.fail_file(path),
+    }
+    result
+}
+
+/// Temporary file a download is written to before being renamed into place
+pub fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// Stream `url` into `<path>.part`, hashing as it arrives, and rename it to `path` only once
+/// the size and SHA-1 match. The final path never holds a partial or corrupt file.
+///
+/// A `.part` file left by an interrupted download is resumed with a `Range` request; servers
+/// that ignore or reject the range get a full download instead. `on_chunk` is called with the
+/// length of every chunk written, including the already downloaded prefix when resuming.
+pub async fn download_resumable(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    expected_sha1: Option<&str>,
+    expected_size: Option<u64>,
+    mut on_chunk: impl FnMut(u64),
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    let mut file = fs::File::create(path)?;
-    file.write_all(&bytes)?;
+
+    let part = part_path(path);
+    let existing = fs::metadata(&part).map(|meta| meta.len()).unwrap_or(0);
+
+    // Everything already arrived last time, only the check and rename are missing
+    if existing > 0 && expected_size.is_some_and(|size| existing >= size) {
+        let actual_sha1 = sha1_file(&part)?;
+        on_chunk(existing);
+        return finish_part_file(&part, path, expected_sha1, expected_size, actual_sha1, existing);
+    }
+
+    let (response, offset) = request_from(client, url, existing).await?;
+    on_chunk(offset);
+
+    // Network errors keep the .part file so the next attempt can resume it
+    let (actual_sha1, actual_size) = write_part_file(response, &part, offset, on_chunk).await?;
+    finish_part_file(&part, path, expected_sha1, expected_size, actual_sha1, actual_size)
+}
+
+/// Request `url` starting at byte `offset`. Returns the response and the offset its body
+/// actually starts at, which is 0 whenever the server sends the whole file.
+async fn request_from(client: &Client, url: &str, offset: u64) -> Result<(reqwest::Response, u64)> {
+    use reqwest::header::{CONTENT_RANGE, RANGE};
+    use reqwest::StatusCode;
+
+    if offset > 0 {
+        let response = client
+            .get(url)
+            .header(RANGE, format!("bytes={}-", offset))
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::PARTIAL_CONTENT {
+            let start = response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("bytes "))
+                .and_then(|value| value.split('-').next())
+                .and_then(|start| start.trim().parse::<u64>().ok());
+            if start == Some(offset) {
+                return Ok((response, offset));
+            }
+        } else if response.status() == StatusCode::OK {
+            // Range not supported, this is the whole file
+            return Ok((response, 0));
+        }
+        // 416 or a range we didn't ask for: the .part file can't be trusted, start over
+    }
+
+    let response = client.get(url).send().await?.error_for_status()?;
+    Ok((response, 0))
+}
+
+/// Append the body to `part` (truncating it first when `offset` is 0).
+/// Returns the SHA-1 and length of the whole part file.
+async fn write_part_file(
+    response: reqwest::Response,
+    part: &Path,
+    offset: u64,
+    mut on_chunk: impl FnMut(u64),
+) -> Result<(String, u64)> {
+    use futures::StreamExt;
+
+    let mut hasher = Sha1::new();
+    let mut file = if offset > 0 {
+        hash_file_into(part, &mut hasher)?;
+        fs::OpenOptions::new().append(true).open(part)?
+    } else {
+        fs::File::create(part)?
+    };
+    let mut written = offset;
+    let mut body = response.bytes_stream();
+
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk?;
+        hasher.update(&chunk);
+        file.write_all(&chunk)?;
+        written += chunk.len() as u64;
+        on_chunk(chunk.len() as u64);
+    }
+    file.sync_all()?;
+
+    Ok((format!("{:x}", hasher.finalize()), written))
+}
+
+/// Stream the contents of `path` into `hasher`
+pub fn hash_file_into(path: &Path, hasher: &mut Sha1) -> std::io::Result<()> {
+    use std::io::Read;
+
+    let mut file = fs::File::open(path)?;
+    let mut buffer = [0; 8192];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(());
+        }
+        hasher.update(&buffer[..n]);
+    }
+}
+
+/// Lowercase hex SHA-1 of the file at `path`
+pub fn sha1_file(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha1::new();
+    hash_file_into(path, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Rename a completed part file into place, or delete it if it doesn't match
+fn finish_part_file(
+    part: &Path,
+    path: &Path,
+    expected_sha1: Option<&str>,
+    expected_size: Option<u64>,
+    actual_sha1: String,
+    actual_size: u64,
+) -> Result<()> {
+    let mismatch = match (expected_size, expected_sha1) {
+        (Some(expected), _) if expected != actual_size => {
+            Some((format!("{} bytes", expected), format!("{} bytes", actual_size)))
+        }
+        (_, Some(expected)) if !expected.eq_ignore_ascii_case(&actual_sha1) => {
+            Some((expected.to_string(), actual_sha1))
+        }
+        _ => None,
+    };
+    if let Some((expected, actual)) = mismatch {
+        let _ = fs::remove_file(part);
+        return Err(LauncherError::HashMismatch {
+            file: path.to_string_lossy().to_string(),
+            expected,
+            actual,
+        });
+    }
+
+    fs::rename(part, path)?;
     Ok(())
 }
 
+/// Official URL of an asset object
+pub fn asset_object_url(hash: &str) -> String {
+    Endpoint::MojangResources.url(&format!("{}/{}", &hash[0..2], hash))
+}
+
+/// Download one asset object, going through the configured mirrors in order on each
+/// attempt and retrying with a short backoff
+async fn download_asset(downloader: &Downloader, path: &Path, object: &AssetObject) -> Result<()> {
+    // Rehashes the linked object and drops it from the store if it is corrupt
+    if downloader.link_from_store(path, Some(&object.hash), Some(object.size)).await {
+        downloader.progress().skip(path, Some(object.size));
+        return Ok(());
+    }
+
+    let urls = downloader.endpoints().candidates(&asset_object_url(&object.hash));
+    let mut attempt = 0;
+    loop {
+        let mut last_error = None;
+        for url in &urls {
+            let result = download_file_checked(
+                downloader.client(),
+                url,
+                path,
+                Some(&object.hash),
+                Some(object.size),
+                Some(downloader.progress()),
+            )
+            .await;
+            match result {
+                Ok(()) => {
+                    downloader.share(path, Some(&object.hash));
+                    return Ok(());
+                }
+                Err(LauncherError::Network(e)) => last_error = Some(download_error(url, e)),
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        if attempt >= downloader.limits().max_retries {
+            return Err(last_error.expect("candidates() always returns at least one URL"));
+        }
+        attempt += 1;
+        tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+    }
+}
+
+/// Download every missing object of an asset index, at most `concurrent_downloads` at a time.
+/// Objects that still fail after `max_retries` are reported together in one error, unless
+/// every failure was a connection error: that is reported as `Network`, i.e. offline.
+/// Legacy indexes then get their virtual/resources tree materialized; the returned
+/// path is the `${game_assets}` directory for those versions.
 pub async fn download_assets(
-    client: &reqwest::Client,
-    assets_index_path: &str,
-    base_dir: &str,
+    downloader: &Downloader,
+    index_id: &str,
+    game_dir: &GameDirectory,
     window: tauri::Window,
-) -> Result<()> {
-    use futures::stream::{FuturesUnordered, StreamExt};
-    use serde_json::Value;
-    use std::sync::Arc;
-    use tokio::sync::Mutex;
-    let index_str = std::fs::read_to_string(assets_index_path)?;
-    let index_json: Value = serde_json::from_str(&index_str)?;
-    let objects = &index_json["objects"];
-    let total = objects.as_object().unwrap().len();
-    let progress = Arc::new(Mutex::new(0));
-    let mut futures = FuturesUnordered::new();
-
-    for (asset_name, asset_info) in objects.as_object().unwrap() {
-        let hash = asset_info["hash"].as_str().unwrap().to_owned();
-        let subdir = hash[0..2].to_string();
-        let asset_url = format!(
-            "https://resources.download.minecraft.net/{}/{}",
-            subdir, hash
-        );
-        let asset_path = format!("{}/assets/objects/{}/{}", base_dir, subdir, hash);
+) -> Result<Option<PathBuf>> {
+    let index = read_asset_index(game_dir, index_id)?;
 
-        if !std::path::Path::new(&asset_path).exists() {
-            let client = client.clone();
-            let window = window.clone();
-            let asset_name = asset_name.clone();
-            let progress = Arc::clone(&progress);
-            futures.push(tokio::spawn(async move {
-                let _ = download_file(&client, &asset_url, &asset_path).await;
-                let mut prog = progress.lock().await;
-                *prog += 1;
-                let _ = window.emit("progress", *prog as f64 / total as f64);
-                let _ = window.emit("log", format!("Downloaded asset: {}", asset_name));
-                Ok::<(), LauncherError>(())
-            }));
+    // One download per object: names sharing a hash share the file, and two concurrent
+    // downloads of it would write the same `.part`
+    let mut missing: BTreeMap<String, (Vec<String>, AssetObject)> = BTreeMap::new();
+    for (name, object) in &index.objects {
+        let path = game_dir.asset_object_path(&object.hash);
+        // Present objects are only size-checked here, so they are not shared; game file
+        // verification rehashes them and seeds the store
+        if !object.is_present(&path) {
+            missing
+                .entry(object.hash.clone())
+                .or_insert_with(|| (Vec::new(), object.clone()))
+                .0
+                .push(name.clone());
         }
     }
+    download_asset_objects(downloader, missing.into_values().collect(), game_dir, &window).await?;
+
+    let legacy_dir = materialize_legacy_assets(&index, game_dir, index_id)?;
+    if let Some(dir) = &legacy_dir {
+        let _ = window.emit("log", format!("Copied legacy assets to {}", dir.display()));
+    }
+    Ok(legacy_dir)
+}
+
+async fn download_asset_objects(
+    downloader: &Downloader,
+    missing: Vec<(Vec<String>, AssetObject)>,
+    game_dir: &GameDirectory,
+    window: &tauri::Window,
+) -> Result<()> {
+    use futures::stream::{self, StreamExt};
+
+    for (_, object) in &missing {
+        downloader.progress().plan(&game_dir.asset_object_path(&object.hash), Some(object.size));
+    }
 
-    while let Some(result) = futures.next().await {
+    let mut downloads = stream::iter(missing)
+        .map(|(asset_names, object)| {
+            let asset_path = game_dir.asset_object_path(&object.hash);
+            async move {
+                let result = download_asset(downloader, &asset_path, &object).await;
+                (asset_names, result)
+            }
+        })
+        .buffer_unordered(downloader.limits().concurrent_downloads);
+
+    let mut failed = Vec::new();
+    let mut connection_error = None;
+    let mut only_connection_errors = true;
+    while let Some((asset_names, result)) = downloads.next().await {
         match result {
-            Ok(_) => {}
+            Ok(()) => {
+                let _ = window.emit("log", format!("Downloaded asset: {}", asset_names.join(", ")));
+            }
             Err(e) => {
-                let _ = window
-                    .emit("error", format!("Error downloading assets: {}", e))
-                    .ok();
+                let _ = window.emit("log", format!("Failed to download asset {}: {}", asset_names.join(", "), e));
+                failed.extend(asset_names);
+                // download_asset keeps only connection failures as `Network`
+                match e {
+                    LauncherError::Network(_) => connection_error = connection_error.or(Some(e)),
+                    _ => only_connection_errors = false,
+                }
             }
         }
     }
-    let _ = window.emit("progress", 1.0);
+
+    if !failed.is_empty() {
+        if let (true, Some(e)) = (only_connection_errors, connection_error) {
+            return Err(e);
+        }
+        failed.sort();
+        return Err(LauncherError::AssetDownloadFailed { failed });
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{context, response, truncated_response, StubServer};
+
+    fn library(json: &str) -> Library {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn argument(json: &str) -> Argument {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn rules(json: &str) -> Vec<Rule> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn lwjgl_disallowed_on_osx() {
+        // 1.12.2: LWJGL 2.9.4 is replaced by an osx-only 2.9.2 build
+        let lwjgl = library(
+            r#"{
+                "name": "org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209",
+                "rules": [{"action": "allow"}, {"action": "disallow", "os": {"name": "osx"}}]
+            }"#,
+        );
+
+        assert!(lwjgl.is_allowed(&context("linux", "x86_64")));
+        assert!(lwjgl.is_allowed(&context("windows", "x86_64")));
+        assert!(!lwjgl.is_allowed(&context("osx", "x86_64")));
+    }
+
+    #[test]
+    fn natives_allow_lists() {
+        let osx_only = library(
+            r#"{
+                "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.2-nightly-20140822",
+                "natives": {"linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows-${arch}"},
+                "rules": [{"action": "allow", "os": {"name": "osx"}}]
+            }"#,
+        );
+        assert!(osx_only.is_allowed(&context("osx", "x86_64")));
+        assert!(!osx_only.is_allowed(&context("linux", "x86_64")));
+
+        // 1.19+: one entry per natives classifier, each restricted to its OS and arch
+        let linux_natives = library(
+            r#"{
+                "name": "org.lwjgl:lwjgl:3.3.1:natives-linux",
+                "rules": [{"action": "allow", "os": {"name": "linux"}}]
+            }"#,
+        );
+        assert!(linux_natives.is_allowed(&context("linux", "x86_64")));
+        assert!(!linux_natives.is_allowed(&context("windows", "x86_64")));
+
+        let arm_natives = library(
+            r#"{
+                "name": "org.lwjgl:lwjgl:3.3.1:natives-macos-arm64",
+                "rules": [{"action": "allow", "os": {"name": "osx", "arch": "arm64"}}]
+            }"#,
+        );
+        assert!(arm_natives.is_allowed(&context("osx", "arm64")));
+        assert!(!arm_natives.is_allowed(&context("osx", "x86_64")));
+    }
+
+    #[test]
+    fn native_classifier_substitutes_arch() {
+        let platform = library(
+            r#"{
+                "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+                "natives": {"linux": "natives-linux", "windows": "natives-windows-${arch}"}
+            }"#,
+        );
+
+        assert_eq!(platform.native_classifier(&context("windows", "x86")).as_deref(), Some("natives-windows-32"));
+        assert_eq!(platform.native_classifier(&context("windows", "x86_64")).as_deref(), Some("natives-windows-64"));
+        assert_eq!(platform.native_classifier(&context("linux", "x86_64")).as_deref(), Some("natives-linux"));
+        assert_eq!(platform.native_classifier(&context("osx", "x86_64")), None);
+    }
+
+    #[test]
+    fn x86_jvm_argument() {
+        let stack_size = argument(r#"{"rules": [{"action": "allow", "os": {"arch": "x86"}}], "value": "-Xss1M"}"#);
+
+        assert_eq!(stack_size.values(&context("windows", "x86")), vec!["-Xss1M"]);
+        assert!(stack_size.values(&context("windows", "x86_64")).is_empty());
+    }
+
+    #[test]
+    fn feature_arguments() {
+        let demo = argument(r#"{"rules": [{"action": "allow", "features": {"is_demo_user": true}}], "value": "--demo"}"#);
+        let resolution = argument(
+            r#"{
+                "rules": [{"action": "allow", "features": {"has_custom_resolution": true}}],
+                "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]
+            }"#,
+        );
+
+        let plain = context("linux", "x86_64");
+        assert!(demo.values(&plain).is_empty());
+        assert!(resolution.values(&plain).is_empty());
+
+        let demo_user = context("linux", "x86_64").with_feature("is_demo_user", true);
+        assert_eq!(demo.values(&demo_user), vec!["--demo"]);
+        assert!(resolution.values(&demo_user).is_empty());
+
+        let custom_resolution = context("linux", "x86_64").with_feature("has_custom_resolution", true);
+        assert_eq!(
+            resolution.values(&custom_resolution),
+            vec!["--width", "${resolution_width}", "--height", "${resolution_height}"]
+        );
+
+        // A feature explicitly turned off is the same as an unknown one
+        let demo_off = context("linux", "x86_64").with_feature("is_demo_user", false);
+        assert!(demo.values(&demo_off).is_empty());
+    }
+
+    #[test]
+    fn os_version_pattern() {
+        let old_osx = argument(
+            r#"{"rules": [{"action": "allow", "os": {"name": "osx", "version": "^10\\.5\\.\\d$"}}], "value": "-Dos.name=Mac OS X 10.5"}"#,
+        );
+
+        let mut leopard = context("osx", "x86_64");
+        leopard.os_version = Some("10.5.8".to_string());
+        assert_eq!(old_osx.values(&leopard), vec!["-Dos.name=Mac OS X 10.5"]);
+
+        let mut sonoma = context("osx", "x86_64");
+        sonoma.os_version = Some("14.2".to_string());
+        assert!(old_osx.values(&sonoma).is_empty());
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let allow_then_disallow = rules(r#"[{"action": "allow"}, {"action": "disallow", "os": {"name": "osx"}}]"#);
+        assert!(!rules_allow(&allow_then_disallow, &context("osx", "x86_64")));
+
+        let disallow_then_allow = rules(r#"[{"action": "disallow", "os": {"name": "osx"}}, {"action": "allow"}]"#);
+        assert!(rules_allow(&disallow_then_allow, &context("osx", "x86_64")));
+
+        let nothing_matches = rules(r#"[{"action": "allow", "os": {"name": "windows"}}]"#);
+        assert!(!rules_allow(&nothing_matches, &context("linux", "x86_64")));
+    }
+
+    #[test]
+    fn empty_rules_allow() {
+        assert!(rules_allow(&[], &context("linux", "x86_64")));
+        assert!(library(r#"{"name": "com.mojang:brigadier:1.0.18"}"#).is_allowed(&context("osx", "arm64")));
+        assert!(library(r#"{"name": "com.mojang:brigadier:1.0.18", "rules": []}"#).is_allowed(&context("osx", "arm64")));
+    }
+
+    const BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    async fn resume_from(part_contents: &[u8], responses: Vec<Vec<u8>>) -> (tempfile::TempDir, PathBuf, StubServer, Result<()>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.jar");
+        if !part_contents.is_empty() {
+            fs::write(part_path(&path), part_contents).unwrap();
+        }
+        let server = StubServer::start(responses).await;
+        let result = download_resumable(
+            &Client::new(),
+            &server.url,
+            &path,
+            Some(&sha1_hex(BODY)),
+            Some(BODY.len() as u64),
+            |_| {},
+        )
+        .await;
+        (dir, path, server, result)
+    }
+
+    #[tokio::test]
+    async fn resumes_after_dropped_connection() {
+        let half = BODY.len() / 2;
+        let content_range = format!("bytes {}-{}/{}", half, BODY.len() - 1, BODY.len());
+        let server = StubServer::start(vec![
+            truncated_response("200 OK", &[], &BODY[..half], BODY.len()),
+            response("206 Partial Content", &[("Content-Range", &content_range)], &BODY[half..]),
+        ])
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.jar");
+        let client = Client::new();
+        let sha1 = sha1_hex(BODY);
+        let download = || download_resumable(&client, &server.url, &path, Some(&sha1), Some(BODY.len() as u64), |_| {});
+
+        assert!(download().await.is_err());
+        assert!(!path.exists());
+        assert_eq!(fs::read(part_path(&path)).unwrap(), &BODY[..half]);
+
+        download().await.unwrap();
+        assert_eq!(fs::read(&path).unwrap(), BODY);
+        assert!(!part_path(&path).exists());
+
+        let requests = server.requests();
+        assert!(!requests[0].contains("range:"));
+        assert!(requests[1].contains(&format!("range: bytes={}-", half)));
+    }
+
+    #[tokio::test]
+    async fn restarts_when_range_is_ignored() {
+        let (_dir, path, server, result) =
+            resume_from(b"stale prefix", vec![response("200 OK", &[], BODY)]).await;
+
+        result.unwrap();
+        assert_eq!(fs::read(&path).unwrap(), BODY);
+        assert!(!part_path(&path).exists());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn discards_part_file_on_416() {
+        let (_dir, path, server, result) = resume_from(
+            b"stale prefix",
+            vec![
+                response("416 Range Not Satisfiable", &[], b""),
+                response("200 OK", &[], BODY),
+            ],
+        )
+        .await;
+
+        result.unwrap();
+        assert_eq!(fs::read(&path).unwrap(), BODY);
+        assert!(!part_path(&path).exists());
+        let requests = server.requests();
+        assert!(requests[0].contains("range: bytes=12-"));
+        assert!(!requests[1].contains("range:"));
+    }
+
+    #[tokio::test]
+    async fn restarts_on_unexpected_content_range() {
+        let wrong_range = format!("bytes 0-{}/{}", BODY.len() - 1, BODY.len());
+        let (_dir, path, server, result) = resume_from(
+            &BODY[..10],
+            vec![
+                response("206 Partial Content", &[("Content-Range", &wrong_range)], BODY),
+                response("200 OK", &[], BODY),
+            ],
+        )
+        .await;
+
+        result.unwrap();
+        assert_eq!(fs::read(&path).unwrap(), BODY);
+        assert!(!part_path(&path).exists());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn corrupt_store_object_is_not_linked() {
+        let body = b"asset object contents";
+        let object = AssetObject {
+            hash: sha1_hex(body),
+            size: body.len() as u64,
+        };
+        let server = StubServer::start(vec![response("200 OK", &[], body)]).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = crate::config::LauncherConfig {
+            shared_store_dir: Some(dir.path().join("store").to_string_lossy().to_string()),
+            mirrors: HashMap::from([(Endpoint::MojangResources, vec![server.base.clone()])]),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let downloader = Downloader::from_config(&config).unwrap();
+        let store = downloader.store().unwrap();
+
+        // Same size, different bytes: passes a size check but not a rehash
+        let stored = store.object_path(&object.hash);
+        fs::create_dir_all(stored.parent().unwrap()).unwrap();
+        fs::write(&stored, vec![b'x'; body.len()]).unwrap();
+
+        let path = dir.path().join("game").join("objects").join(&object.hash);
+        download_asset(&downloader, &path, &object).await.unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), body);
+        assert_eq!(fs::read(&stored).unwrap(), body);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unreachable_asset_mirror_is_a_connection_error() {
+        // Bound and released again, so nothing listens there
+        let dead = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let config = crate::config::LauncherConfig {
+            use_shared_store: false,
+            mirrors: HashMap::from([(Endpoint::MojangResources, vec![format!("http://{}", dead)])]),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let downloader = Downloader::from_config(&config).unwrap();
+        let object = AssetObject {
+            hash: sha1_hex(b"asset"),
+            size: 5,
+        };
+
+        let result = download_asset(&downloader, &dir.path().join("object"), &object).await;
+        assert!(matches!(result, Err(LauncherError::Network(_))));
+    }
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::fs;
+use crate::config::LauncherConfig;
+use crate::endpoints::{Endpoint, EndpointRegistry};
 use crate::errors::LauncherError;
+use crate::http;
 
 // Modrinth API response structures
 #[derive(Debug, Deserialize)]
@@ -215,24 +218,21 @@ impl ModManager {
         self.ensure_mods_directory()?;
         
         // Get project info from Modrinth
-        let client = reqwest::Client::new();
+        let config = LauncherConfig::load().unwrap_or_default();
+        let client = http::build_client(&config)?;
+        let endpoints = EndpointRegistry::from_config(&config);
         
         progress_callback(10);
         
         // First, get the project to get the correct project ID
-        let project_url = format!("https://api.modrinth.com/v2/project/{}", mod_id);
-        let project_response = client
-            .get(&project_url)
-            .header("User-Agent", "Satellite-Launcher/0.3.0 (contact@satellite-launcher.com)")
-            .send()
-            .await?;
-            
-        if !project_response.status().is_success() {
-            return Err(LauncherError::DownloadFailed {
-                url: project_url,
-                reason: format!("Failed to get project info: {}", project_response.status()),
-            });
-        }
+        let project_url = Endpoint::Modrinth.url(&format!("v2/project/{}", mod_id));
+        let project_response = endpoints
+            .get(&client, &project_url)
+            .await
+            .map_err(|e| LauncherError::DownloadFailed {
+                url: project_url.clone(),
+                reason: format!("Failed to get project info: {}", e),
+            })?;
         
         let project: serde_json::Value = project_response.json().await?;
         let project_id = project["id"].as_str().unwrap_or(mod_id);
@@ -240,19 +240,14 @@ impl ModManager {
         progress_callback(20);
         
         // Get versions for this project
-        let versions_url = format!("https://api.modrinth.com/v2/project/{}/version", project_id);
-        let versions_response = client
-            .get(&versions_url)
-            .header("User-Agent", "Satellite-Launcher/0.3.0 (contact@satellite-launcher.com)")
-            .send()
-            .await?;
-            
-        if !versions_response.status().is_success() {
-            return Err(LauncherError::DownloadFailed {
-                url: versions_url,
-                reason: format!("Failed to get versions: {}", versions_response.status()),
-            });
-        }
+        let versions_url = Endpoint::Modrinth.url(&format!("v2/project/{}/version", project_id));
+        let versions_response = endpoints
+            .get(&client, &versions_url)
+            .await
+            .map_err(|e| LauncherError::DownloadFailed {
+                url: versions_url.clone(),
+                reason: format!("Failed to get versions: {}", e),
+            })?;
         
         let versions: Vec<serde_json::Value> = versions_response.json().await?;
         
@@ -317,26 +312,19 @@ impl ModManager {
         
         progress_callback(50);
         
-        // Download the file
-        let download_response = client
-            .get(download_url)
-            .header("User-Agent", "Satellite-Launcher/0.3.0 (contact@satellite-launcher.com)")
-            .send()
-            .await?;
-            
-        if !download_response.status().is_success() {
-            return Err(LauncherError::DownloadFailed {
-                url: download_url.to_string(),
-                reason: format!("Download failed: {}", download_response.status()),
-            });
-        }
-        
-        progress_callback(70);
-        
-        // Save the file
+        // Download the file, resuming a previous partial download if there is one, and
+        // check it against the SHA-1 Modrinth publishes
         let destination = self.mods_directory.join(filename);
-        let bytes = download_response.bytes().await?;
-        fs::write(&destination, bytes)?;
+        let expected_sha1 = primary_file["hashes"]["sha1"].as_str();
+        let expected_size = primary_file["size"].as_u64();
+        let mut received = 0u64;
+        crate::minecraft_api::download_resumable(&client, download_url, &destination, expected_sha1, expected_size, |n| {
+            received += n;
+            if let Some(total) = expected_size.filter(|total| *total > 0) {
+                progress_callback(50 + (received.min(total) * 40 / total) as u8);
+            }
+        })
+        .await?;
         
         progress_callback(90);
         
@@ -361,7 +349,9 @@ pub async fn search_mods(
         return Ok(Vec::new());
     }
 
-    let client = reqwest::Client::new();
+    let config = LauncherConfig::load().unwrap_or_default();
+    let client = http::build_client(&config)?;
+    let endpoints = EndpointRegistry::from_config(&config);
     
     // Convert our ModLoader enum to Modrinth's format
     let loader_filter = match mod_loader {
@@ -373,29 +363,24 @@ pub async fn search_mods(
     
     // Build the search URL with more flexible filters
     // Only filter by project type (mod) and include version/loader as optional
-    let url = format!(
-        "https://api.modrinth.com/v2/search?query={}&limit={}&facets=[[\"project_type:mod\"], [\"versions:{}\"], [\"categories:{}\"]]",
+    let url = Endpoint::Modrinth.url(&format!(
+        "v2/search?query={}&limit={}&facets=[[\"project_type:mod\"], [\"versions:{}\"], [\"categories:{}\"]]",
         urlencoding::encode(query),
         limit,
         minecraft_version,
         loader_filter
-    );
+    ));
     
     println!("Searching Modrinth with URL: {}", url); // Debug log
     println!("Filters: minecraft_version={}, mod_loader={}", minecraft_version, loader_filter);
     
-    let response = client
-        .get(&url)
-        .header("User-Agent", "Satellite-Launcher/0.3.0 (contact@satellite-launcher.com)")
-        .send()
-        .await?;
-    
-    if !response.status().is_success() {
-        return Err(LauncherError::DownloadFailed {
+    let response = endpoints
+        .get(&client, &url)
+        .await
+        .map_err(|e| LauncherError::DownloadFailed {
             url: url.clone(),
-            reason: format!("Modrinth API returned status: {}", response.status()),
-        });
-    }
+            reason: format!("Modrinth API request failed: {}", e),
+        })?;
     
     let search_response: ModrinthSearchResponse = response.json().await?;
     
@@ -0,0 +1,199 @@
+use crate::minecraft_api::{Library, RuleContext};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Separator the JVM expects between classpath entries on this OS
+pub const CLASSPATH_SEPARATOR: &str = if cfg!(windows) { ";" } else { ":" };
+
+/// Prefix of the per-launch natives directories inside a version directory
+pub const NATIVES_DIR_PREFIX: &str = "natives-";
+
+/// Standard locations inside a game directory
+#[derive(Debug, Clone)]
+pub struct GameDirectory {
+    root: PathBuf,
+}
+
+impl GameDirectory {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn libraries_dir(&self) -> PathBuf {
+        self.root.join("libraries")
+    }
+
+    /// Absolute path of a library given its `/` separated path relative to `libraries/`
+    pub fn library_path(&self, relative: &str) -> PathBuf {
+        join_relative(&self.libraries_dir(), relative)
+    }
+
+    pub fn versions_dir(&self) -> PathBuf {
+        self.root.join("versions")
+    }
+
+    pub fn version_dir(&self, id: &str) -> PathBuf {
+        self.versions_dir().join(id)
+    }
+
+    pub fn version_json_path(&self, id: &str) -> PathBuf {
+        self.version_dir(id).join(format!("{}.json", id))
+    }
+
+    pub fn version_jar_path(&self, id: &str) -> PathBuf {
+        self.version_dir(id).join(format!("{}.jar", id))
+    }
+
+    /// New natives directory for one launch, `versions/<id>/natives-<pid>-<millis>` with the
+    /// launcher's pid, so two running instances of a version never share each other's natives
+    pub fn new_natives_dir(&self, id: &str) -> PathBuf {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or_default();
+        self.version_dir(id)
+            .join(format!("{}{}-{}", NATIVES_DIR_PREFIX, std::process::id(), millis))
+    }
+
+    pub fn assets_dir(&self) -> PathBuf {
+        self.root.join("assets")
+    }
+
+    pub fn asset_index_path(&self, id: &str) -> PathBuf {
+        self.assets_dir().join("indexes").join(format!("{}.json", id))
+    }
+
+    pub fn asset_object_path(&self, hash: &str) -> PathBuf {
+        self.assets_dir().join("objects").join(&hash[0..2]).join(hash)
+    }
+
+    /// Named copy of a `"virtual": true` asset index, e.g. `assets/virtual/legacy`
+    pub fn virtual_assets_dir(&self, index_id: &str) -> PathBuf {
+        self.assets_dir().join("virtual").join(index_id)
+    }
+
+    /// Named copy of a `"map_to_resources": true` asset index
+    pub fn resources_dir(&self) -> PathBuf {
+        self.root.join("resources")
+    }
+}
+
+/// Join a `/` separated relative path onto `base` component by component
+pub fn join_relative(base: &Path, relative: &str) -> PathBuf {
+    relative
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .fold(base.to_path_buf(), |path, part| path.join(part))
+}
+
+/// Classpath entries for the libraries that apply to `context`, followed by the client jar.
+/// Entries keep their first position and later duplicates are dropped.
+pub fn build_classpath(
+    libraries: &[Library],
+    game_dir: &GameDirectory,
+    client_jar: &Path,
+    context: &RuleContext,
+) -> Vec<PathBuf> {
+    let entries = libraries
+        .iter()
+        .filter(|lib| lib.is_allowed(context))
+        .filter_map(|lib| lib.artifact_path())
+        .map(|relative| game_dir.library_path(&relative))
+        .chain(std::iter::once(client_jar.to_path_buf()));
+
+    dedup_paths(entries)
+}
+
+/// Remove duplicate paths while keeping the order of first occurrence
+pub fn dedup_paths(paths: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|path| seen.insert(path.clone()))
+        .collect()
+}
+
+/// Join classpath entries with the platform separator
+pub fn join_classpath(entries: &[PathBuf]) -> String {
+    entries
+        .iter()
+        .map(|entry| entry.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(CLASSPATH_SEPARATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::context;
+
+    fn libraries(json: &str) -> Vec<Library> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn classpath_filters_dedups_and_ends_with_client_jar() {
+        let libraries = libraries(
+            r#"[
+                {"name": "com.mojang:patchy:1.1"},
+                {
+                    "name": "org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209",
+                    "rules": [{"action": "allow"}, {"action": "disallow", "os": {"name": "osx"}}]
+                },
+                {
+                    "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+                    "natives": {"linux": "natives-linux", "osx": "natives-osx"},
+                    "downloads": {"classifiers": {}}
+                },
+                {"name": "com.google.guava:guava:21.0"},
+                {"name": "com.mojang:patchy:1.1"}
+            ]"#,
+        );
+        let game_dir = GameDirectory::new("/game");
+        let client_jar = game_dir.version_jar_path("1.12.2");
+
+        let linux = build_classpath(&libraries, &game_dir, &client_jar, &context("linux", "x86_64"));
+        assert_eq!(
+            linux,
+            vec![
+                game_dir.library_path("com/mojang/patchy/1.1/patchy-1.1.jar"),
+                game_dir.library_path("org/lwjgl/lwjgl/lwjgl/2.9.4-nightly-20150209/lwjgl-2.9.4-nightly-20150209.jar"),
+                game_dir.library_path("com/google/guava/guava/21.0/guava-21.0.jar"),
+                client_jar.clone(),
+            ]
+        );
+
+        // The LWJGL rule removes it on osx; the natives-only entry never joins the classpath
+        let osx = build_classpath(&libraries, &game_dir, &client_jar, &context("osx", "x86_64"));
+        assert_eq!(
+            osx,
+            vec![
+                game_dir.library_path("com/mojang/patchy/1.1/patchy-1.1.jar"),
+                game_dir.library_path("com/google/guava/guava/21.0/guava-21.0.jar"),
+                client_jar,
+            ]
+        );
+    }
+
+    #[test]
+    fn join_classpath_uses_platform_separator() {
+        let entries = vec![PathBuf::from("a.jar"), PathBuf::from("b.jar")];
+        assert_eq!(join_classpath(&entries), format!("a.jar{}b.jar", CLASSPATH_SEPARATOR));
+        assert_eq!(join_classpath(&[]), "");
+    }
+
+    #[test]
+    fn join_relative_splits_on_slashes() {
+        let base = Path::new("libraries");
+        assert_eq!(
+            join_relative(base, "com/mojang/patchy/1.1/patchy-1.1.jar"),
+            base.join("com").join("mojang").join("patchy").join("1.1").join("patchy-1.1.jar")
+        );
+        assert_eq!(join_relative(base, "/a//b/"), base.join("a").join("b"));
+    }
+}
@@ -0,0 +1,190 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// Minimum time between two `download_progress` events, so chunk callbacks don't flood the UI
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Payload of the `download_progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    /// File the event was triggered by
+    pub file: String,
+    pub file_bytes: u64,
+    pub file_total: Option<u64>,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_per_second: f64,
+    pub eta_seconds: Option<f64>,
+}
+
+struct ProgressState {
+    window: Option<tauri::Window>,
+    planned: HashSet<PathBuf>,
+    active: HashMap<PathBuf, (u64, Option<u64>)>,
+    files_done: usize,
+    files_total: usize,
+    bytes_done: u64,
+    bytes_total: u64,
+    last_emit: Instant,
+    last_bytes: u64,
+    speed: f64,
+}
+
+impl ProgressState {
+    fn new(window: Option<tauri::Window>) -> Self {
+        Self {
+            window,
+            planned: HashSet::new(),
+            active: HashMap::new(),
+            files_done: 0,
+            files_total: 0,
+            bytes_done: 0,
+            bytes_total: 0,
+            last_emit: Instant::now(),
+            last_bytes: 0,
+            speed: 0.0,
+        }
+    }
+
+    /// Build an event payload if one is due, updating the smoothed speed
+    fn snapshot(&mut self, path: &Path, force: bool) -> Option<(tauri::Window, DownloadProgress)> {
+        let window = self.window.clone()?;
+        let elapsed = self.last_emit.elapsed();
+        if !force && elapsed < EMIT_INTERVAL {
+            return None;
+        }
+
+        if elapsed.as_secs_f64() > 0.0 {
+            let sample = self.bytes_done.saturating_sub(self.last_bytes) as f64 / elapsed.as_secs_f64();
+            self.speed = if self.speed == 0.0 { sample } else { self.speed * 0.7 + sample * 0.3 };
+        }
+        self.last_emit = Instant::now();
+        self.last_bytes = self.bytes_done;
+
+        let (file_bytes, file_total) = self.active.get(path).copied().unwrap_or((0, None));
+        let remaining = self.bytes_total.saturating_sub(self.bytes_done);
+        let eta_seconds = (self.speed > 0.0).then(|| remaining as f64 / self.speed);
+
+        Some((
+            window,
+            DownloadProgress {
+                file: path.to_string_lossy().to_string(),
+                file_bytes,
+                file_total,
+                bytes_done: self.bytes_done,
+                bytes_total: self.bytes_total,
+                files_done: self.files_done,
+                files_total: self.files_total,
+                bytes_per_second: self.speed,
+                eta_seconds,
+            },
+        ))
+    }
+}
+
+/// Aggregates byte-level progress of every download in one launch preparation and
+/// publishes it as a single `download_progress` event.
+///
+/// Files are either planned up front (so the bar has a stable total) or added when
+/// their download starts.
+pub struct ProgressTracker {
+    state: Mutex<ProgressState>,
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(ProgressState::new(None)),
+        }
+    }
+
+    /// Reset the counters and start reporting to `window`
+    pub fn begin(&self, window: &tauri::Window) {
+        *self.state.lock().unwrap() = ProgressState::new(Some(window.clone()));
+    }
+
+    /// Count a file that is going to be downloaded; planning the same path twice is a no-op
+    pub fn plan(&self, path: &Path, size: Option<u64>) {
+        let mut state = self.state.lock().unwrap();
+        if state.planned.insert(path.to_path_buf()) {
+            state.files_total += 1;
+            state.bytes_total += size.unwrap_or(0);
+        }
+    }
+
+    /// A planned file turned out to be up to date after all
+    pub fn skip(&self, path: &Path, size: Option<u64>) {
+        let mut state = self.state.lock().unwrap();
+        if state.planned.remove(path) {
+            state.files_total -= 1;
+            state.bytes_total = state.bytes_total.saturating_sub(size.unwrap_or(0));
+        }
+    }
+
+    pub fn start_file(&self, path: &Path, size: Option<u64>) {
+        self.plan(path, size);
+        let mut state = self.state.lock().unwrap();
+        state.active.insert(path.to_path_buf(), (0, size));
+    }
+
+    pub fn advance(&self, path: &Path, bytes: u64) {
+        let event = {
+            let mut state = self.state.lock().unwrap();
+            state.bytes_done += bytes;
+            // Files without a known size can push past the planned total
+            state.bytes_total = state.bytes_total.max(state.bytes_done);
+            if let Some((done, _)) = state.active.get_mut(path) {
+                *done += bytes;
+            }
+            state.snapshot(path, false)
+        };
+        emit(event);
+    }
+
+    pub fn finish_file(&self, path: &Path) {
+        let event = {
+            let mut state = self.state.lock().unwrap();
+            state.files_done += 1;
+            let event = state.snapshot(path, true);
+            state.active.remove(path);
+            event
+        };
+        emit(event);
+    }
+
+    /// Roll back the bytes of a failed attempt so a retry doesn't count them twice
+    pub fn fail_file(&self, path: &Path) {
+        let mut state = self.state.lock().unwrap();
+        if let Some((done, _)) = state.active.remove(path) {
+            state.bytes_done = state.bytes_done.saturating_sub(done);
+        }
+    }
+
+    /// Emit a final event once everything has been downloaded
+    pub fn complete(&self) {
+        let event = {
+            let mut state = self.state.lock().unwrap();
+            state.bytes_done = state.bytes_done.max(state.bytes_total);
+            state.snapshot(Path::new(""), true)
+        };
+        emit(event);
+    }
+}
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn emit(event: Option<(tauri::Window, DownloadProgress)>) {
+    if let Some((window, progress)) = event {
+        window.emit("download_progress", progress).ok();
+    }
+}
@@ -0,0 +1,436 @@
+use crate::cache::MetadataCache;
+use crate::config::LauncherConfig;
+use crate::downloader::Downloader;
+use crate::endpoints::Endpoint;
+use crate::errors::{LauncherError, LauncherResult};
+use crate::java;
+use crate::minecraft_api::DownloadInfo;
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+
+/// Index of every Java runtime Mojang publishes, per platform and component
+const RUNTIME_INDEX_PATH: &str =
+    "v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+/// Written into an installed runtime once every file is in place; holds the manifest SHA-1
+const INSTALLED_MARKER: &str = ".installed";
+
+/// `all.json`: platform -> component -> releases (normally exactly one)
+type RuntimeIndex = HashMap<String, HashMap<String, Vec<RuntimeRelease>>>;
+
+#[derive(Debug, Deserialize)]
+struct RuntimeRelease {
+    manifest: DownloadInfo,
+    version: RuntimeVersion,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuntimeVersion {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuntimeManifest {
+    files: BTreeMap<String, RuntimeFile>,
+}
+
+/// Entry of a runtime manifest, keyed by its path relative to the runtime root
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum RuntimeFile {
+    File {
+        #[serde(default)]
+        executable: bool,
+        downloads: RuntimeFileDownloads,
+    },
+    Directory,
+    Link {
+        target: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct RuntimeFileDownloads {
+    raw: DownloadInfo,
+    /// Same file, LZMA-compressed; `raw` still describes the decompressed result
+    lzma: Option<DownloadInfo>,
+}
+
+/// Platform key used by Mojang's runtime index for this machine
+pub fn platform() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        ("macos", "x86_64") => Some("mac-os"),
+        ("macos", "aarch64") => Some("mac-os-arm64"),
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "aarch64") => Some("windows-arm64"),
+        _ => None,
+    }
+}
+
+/// Root of the runtime installed for `component`, e.g. `java-runtime-gamma`
+pub fn runtime_home(component: &str) -> PathBuf {
+    java::runtimes_dir().join(component)
+}
+
+/// Java executable of an installed runtime, if it finished installing
+pub fn installed_executable(component: &str) -> Option<PathBuf> {
+    let home = runtime_home(component);
+    if !home.join(INSTALLED_MARKER).exists() {
+        return None;
+    }
+    java::executable_in_home(&home)
+}
+
+/// Download Mojang's Java runtime `component` for this platform under the launcher data
+/// directory and return its Java executable. Files already in place are verified and kept,
+/// so this also repairs and updates an existing install.
+pub async fn install_runtime(
+    downloader: &Downloader,
+    config: &LauncherConfig,
+    component: &str,
+    window: &tauri::Window,
+) -> LauncherResult<PathBuf> {
+    let unavailable = || LauncherError::RuntimeUnavailable {
+        component: component.to_string(),
+        platform: platform().unwrap_or(std::env::consts::OS).to_string(),
+    };
+    let platform = platform().ok_or_else(unavailable)?;
+
+    let cache = MetadataCache::from_config(config);
+    let index_json = cache
+        .fetch(downloader.client(), &Endpoint::MojangMeta.url(RUNTIME_INDEX_PATH))
+        .await?;
+    let index: RuntimeIndex = serde_json::from_str(&index_json)?;
+    let release = index
+        .get(platform)
+        .and_then(|components| components.get(component))
+        .and_then(|releases| releases.first())
+        .ok_or_else(unavailable)?;
+
+    let home = runtime_home(component);
+    let marker = home.join(INSTALLED_MARKER);
+    let manifest_sha1 = release.manifest.sha1.clone().unwrap_or_default();
+    if fs::read_to_string(&marker).is_ok_and(|installed| installed.trim() == manifest_sha1) {
+        if let Some(executable) = java::executable_in_home(&home) {
+            return Ok(executable);
+        }
+    }
+
+    window
+        .emit("log", format!("Installing Java runtime {} ({})...", component, release.version.name))
+        .ok();
+
+    let manifest_path = java::runtimes_dir().join("manifests").join(format!("{}.json", component));
+    fetch_verified(downloader, &release.manifest, &manifest_path).await?;
+    let manifest: RuntimeManifest = serde_json::from_str(&fs::read_to_string(&manifest_path)?)?;
+
+    // Directories first, then files, then links whose targets must already exist
+    let _ = fs::remove_file(&marker);
+    fs::create_dir_all(&home)?;
+    for (name, file) in &manifest.files {
+        if matches!(file, RuntimeFile::Directory) {
+            fs::create_dir_all(home.join(name))?;
+        }
+    }
+
+    let files: Vec<(PathBuf, bool, &RuntimeFileDownloads)> = manifest
+        .files
+        .iter()
+        .filter_map(|(name, file)| match file {
+            RuntimeFile::File { executable, downloads } => Some((home.join(name), *executable, downloads)),
+            _ => None,
+        })
+        .collect();
+
+    let progress = downloader.progress();
+    progress.begin(window);
+    for (path, _, downloads) in &files {
+        match &downloads.lzma {
+            Some(lzma) => progress.plan(&lzma_path(path), lzma.size),
+            None => progress.plan(path, downloads.raw.size),
+        }
+    }
+
+    let mut results = stream::iter(&files)
+        .map(|(path, executable, downloads)| async move {
+            download_runtime_file(downloader, path, downloads).await?;
+            if *executable {
+                set_executable(path)?;
+            }
+            Ok::<_, LauncherError>(())
+        })
+        .buffer_unordered(downloader.limits().concurrent_downloads);
+    while let Some(result) = results.next().await {
+        result?;
+    }
+    progress.complete();
+
+    for (name, file) in &manifest.files {
+        if let RuntimeFile::Link { target } = file {
+            create_link(&home.join(name), target)?;
+        }
+    }
+
+    let executable = java::executable_in_home(&home).ok_or_else(|| {
+        LauncherError::FileNotFound(home.join("bin").join(java::JAVA_BINARY).to_string_lossy().to_string())
+    })?;
+    fs::write(&marker, &manifest_sha1)?;
+    window
+        .emit("log", format!("Java runtime {} installed at {}", component, home.display()))
+        .ok();
+    Ok(executable)
+}
+
+fn lzma_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lzma");
+    PathBuf::from(name)
+}
+
+/// Keep `path` if it matches `download`, otherwise download it, retrying up to `max_retries`
+/// times. Unlike `Downloader::verify_and_download` this bypasses the shared store: its
+/// garbage collection only keeps game files, so runtime files would churn through it.
+async fn fetch_verified(downloader: &Downloader, download: &DownloadInfo, path: &Path) -> LauncherResult<()> {
+    let (sha1, size) = (download.sha1.as_deref(), download.size);
+    if path.exists() && downloader.verify_file_blocking(path, sha1, size).await.is_ok() {
+        downloader.progress().skip(path, size);
+        return Ok(());
+    }
+
+    let mut attempt = 0;
+    loop {
+        match downloader.download_checked(&download.url, path, sha1, size, None).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= downloader.limits().max_retries => return Err(e),
+            Err(_) => attempt += 1,
+        }
+    }
+}
+
+/// Fetch one runtime file, preferring the LZMA variant and falling back to the raw one
+/// when it can't be downloaded or doesn't decompress to the expected bytes
+async fn download_runtime_file(downloader: &Downloader, path: &Path, downloads: &RuntimeFileDownloads) -> LauncherResult<()> {
+    let raw = &downloads.raw;
+    let Some(lzma) = &downloads.lzma else {
+        return fetch_verified(downloader, raw, path).await;
+    };
+
+    let compressed = lzma_path(path);
+    if path.exists() && downloader.verify_file_blocking(path, raw.sha1.as_deref(), raw.size).await.is_ok() {
+        downloader.progress().skip(&compressed, lzma.size);
+        return Ok(());
+    }
+
+    let unpacked = match downloader
+        .download_checked(&lzma.url, &compressed, lzma.sha1.as_deref(), lzma.size, None)
+        .await
+    {
+        Ok(()) => {
+            let (from, to) = (compressed.clone(), path.to_path_buf());
+            let result = tokio::task::spawn_blocking(move || decompress_lzma(&from, &to))
+                .await
+                .map_err(|e| LauncherError::FileSystemError {
+                    operation: "decompress_lzma".to_string(),
+                    path: path.to_string_lossy().to_string(),
+                    error: e.to_string(),
+                })
+                .and_then(|result| result);
+            let _ = fs::remove_file(&compressed);
+            match result {
+                Ok(()) => downloader.verify_file_blocking(path, raw.sha1.as_deref(), raw.size).await,
+                Err(e) => Err(e),
+            }
+        }
+        Err(e) => Err(e),
+    };
+
+    if unpacked.is_err() {
+        let _ = fs::remove_file(path);
+        fetch_verified(downloader, raw, path).await?;
+    }
+    Ok(())
+}
+
+fn decompress_lzma(from: &Path, to: &Path) -> LauncherResult<()> {
+    let mut input = BufReader::new(File::open(from)?);
+    let mut output = BufWriter::new(File::create(to)?);
+    lzma_rs::lzma_decompress(&mut input, &mut output).map_err(|e| LauncherError::FileSystemError {
+        operation: "decompress_lzma".to_string(),
+        path: from.to_string_lossy().to_string(),
+        error: e.to_string(),
+    })
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> LauncherResult<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> LauncherResult<()> {
+    Ok(())
+}
+
+/// Recreate the symlink `path` -> `target`; `target` is relative to the link's directory
+#[cfg(unix)]
+fn create_link(path: &Path, target: &str) -> LauncherResult<()> {
+    if fs::symlink_metadata(path).is_ok() {
+        fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    std::os::unix::fs::symlink(target, path).map_err(|e| LauncherError::FileSystemError {
+        operation: "create_symlink".to_string(),
+        path: path.to_string_lossy().to_string(),
+        error: e.to_string(),
+    })
+}
+
+/// Windows runtimes don't ship links, and creating symlinks there needs extra privileges
+#[cfg(not(unix))]
+fn create_link(_path: &Path, _target: &str) -> LauncherResult<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minecraft_api::sha1_file;
+    use crate::testing::{response, StubServer};
+
+    const JAVA: &[u8] = b"#!/bin/sh\necho java\n";
+
+    fn downloader() -> Downloader {
+        let config = LauncherConfig {
+            max_retries: 0,
+            use_shared_store: false,
+            ..Default::default()
+        };
+        Downloader::from_config(&config).unwrap()
+    }
+
+    fn sha1_of(dir: &Path, contents: &[u8]) -> String {
+        let path = dir.join("hashed");
+        fs::write(&path, contents).unwrap();
+        sha1_file(&path).unwrap()
+    }
+
+    fn compress(contents: &[u8]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress(&mut &contents[..], &mut compressed).unwrap();
+        compressed
+    }
+
+    fn downloads(server: &StubServer, raw_sha1: &str, lzma: Option<&[u8]>) -> RuntimeFileDownloads {
+        let info = |sha1: Option<String>, size: usize| DownloadInfo {
+            url: server.url.clone(),
+            path: None,
+            sha1,
+            size: Some(size as u64),
+        };
+        RuntimeFileDownloads {
+            raw: info(Some(raw_sha1.to_string()), JAVA.len()),
+            lzma: lzma.map(|lzma| info(None, lzma.len())),
+        }
+    }
+
+    #[test]
+    fn parses_manifest_entries() {
+        let manifest: RuntimeManifest = serde_json::from_str(
+            r#"{"files": {
+                "bin": {"type": "directory"},
+                "bin/java": {"type": "file", "executable": true, "downloads": {
+                    "raw": {"url": "https://example.invalid/java", "sha1": "00", "size": 1}
+                }},
+                "legal/java.base/LICENSE": {"type": "link", "target": "../java.desktop/LICENSE"}
+            }}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(manifest.files["bin"], RuntimeFile::Directory));
+        assert!(matches!(
+            &manifest.files["bin/java"],
+            RuntimeFile::File { executable: true, downloads } if downloads.lzma.is_none()
+        ));
+        assert!(matches!(&manifest.files["legal/java.base/LICENSE"], RuntimeFile::Link { target } if target == "../java.desktop/LICENSE"));
+    }
+
+    #[tokio::test]
+    async fn unpacks_the_lzma_variant() {
+        let dir = tempfile::tempdir().unwrap();
+        let compressed = compress(JAVA);
+        let server = StubServer::start(vec![response("200 OK", &[], &compressed)]).await;
+        let downloads = downloads(&server, &sha1_of(dir.path(), JAVA), Some(&compressed));
+
+        let path = dir.path().join("bin").join("java");
+        download_runtime_file(&downloader(), &path, &downloads).await.unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), JAVA);
+        assert!(!lzma_path(&path).exists());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_raw_when_lzma_is_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let garbage = vec![0xff; 32];
+        let server = StubServer::start(vec![
+            response("200 OK", &[], &garbage),
+            response("200 OK", &[], JAVA),
+        ])
+        .await;
+        let downloads = downloads(&server, &sha1_of(dir.path(), JAVA), Some(&garbage));
+
+        let path = dir.path().join("bin").join("java");
+        download_runtime_file(&downloader(), &path, &downloads).await.unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), JAVA);
+        assert!(!lzma_path(&path).exists());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn keeps_a_file_that_already_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = StubServer::start(Vec::new()).await;
+        let downloads = downloads(&server, &sha1_of(dir.path(), JAVA), Some(&compress(JAVA)));
+
+        let path = dir.path().join("java");
+        fs::write(&path, JAVA).unwrap();
+        download_runtime_file(&downloader(), &path, &downloads).await.unwrap();
+
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_and_executables() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let java = dir.path().join("bin").join("java");
+        fs::create_dir_all(java.parent().unwrap()).unwrap();
+        fs::write(&java, JAVA).unwrap();
+        set_executable(&java).unwrap();
+        assert_eq!(fs::metadata(&java).unwrap().permissions().mode() & 0o777, 0o755);
+
+        // Relative to the link's directory, replacing a stale file
+        let link = dir.path().join("lib").join("java");
+        fs::create_dir_all(link.parent().unwrap()).unwrap();
+        fs::write(&link, b"stale").unwrap();
+        create_link(&link, "../bin/java").unwrap();
+        assert_eq!(fs::read_link(&link).unwrap(), Path::new("../bin/java"));
+        assert_eq!(fs::read(&link).unwrap(), JAVA);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::config::LauncherConfig;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Content-addressed store shared by every game directory, laid out as `<root>/<aa>/<sha1>`.
+///
+/// Libraries, client jars and asset objects with a known SHA-1 are kept here once and
+/// hardlinked into each game directory. Files only enter the store as hard links, so a game
+/// directory on another filesystem neither shares nor pays for a second copy.
+pub struct SharedStore {
+    root: PathBuf,
+}
+
+/// Outcome of a store garbage collection
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StoreGcReport {
+    pub removed: usize,
+    /// Combined size of the removed objects
+    pub removed_bytes: u64,
+    pub kept: usize,
+}
+
+impl SharedStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The store configured in `config`, or `None` when sharing is turned off
+    pub fn from_config(config: &LauncherConfig) -> Option<Self> {
+        if !config.use_shared_store {
+            return None;
+        }
+        let root = match &config.shared_store_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Self::default_dir(),
+        };
+        Some(Self::new(root))
+    }
+
+    fn default_dir() -> PathBuf {
+        if let Some(data_dir) = dirs::data_dir() {
+            data_dir.join("satellite-launcher").join("store")
+        } else {
+            PathBuf::from("./store")
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn object_path(&self, sha1: &str) -> PathBuf {
+        let sha1 = sha1.to_ascii_lowercase();
+        self.root.join(&sha1[0..2]).join(sha1)
+    }
+
+    /// Whether the store holds `sha1`, with the expected size when one is known
+    pub fn contains(&self, sha1: &str, size: Option<u64>) -> bool {
+        match fs::metadata(self.object_path(sha1)) {
+            Ok(meta) => size.is_none_or(|size| meta.len() == size),
+            Err(_) => false,
+        }
+    }
+
+    /// Make `path` a link to the stored object, replacing whatever is there
+    pub fn link_into(&self, sha1: &str, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        link_or_copy(&self.object_path(sha1), path)
+    }
+
+    /// Add an already verified file to the store by hard-linking it; a no-op if the object
+    /// is already there. Fails when `path` is on another filesystem than the store.
+    pub fn insert(&self, sha1: &str, path: &Path) -> io::Result<()> {
+        let object = self.object_path(sha1);
+        if object.exists() {
+            return Ok(());
+        }
+        if let Some(parent) = object.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::hard_link(path, &object)
+    }
+
+    /// Drop a stored object, e.g. after it failed verification
+    pub fn remove(&self, sha1: &str) {
+        let _ = fs::remove_file(self.object_path(sha1));
+    }
+
+    /// Delete every object whose hash is not in `referenced`. Game directories keep their
+    /// own links, so this never breaks an installed file; it only stops sharing it.
+    pub fn collect_garbage(&self, referenced: &HashSet<String>) -> io::Result<StoreGcReport> {
+        let mut report = StoreGcReport::default();
+        let Ok(prefixes) = fs::read_dir(&self.root) else {
+            return Ok(report);
+        };
+
+        for prefix in prefixes {
+            let prefix = prefix?.path();
+            if !prefix.is_dir() {
+                continue;
+            }
+            for object in fs::read_dir(&prefix)? {
+                let object = object?;
+                let hash = object.file_name().to_string_lossy().to_ascii_lowercase();
+                if referenced.contains(&hash) {
+                    report.kept += 1;
+                    continue;
+                }
+                let size = object.metadata()?.len();
+                fs::remove_file(object.path())?;
+                report.removed += 1;
+                report.removed_bytes += size;
+            }
+            // Only succeeds once the prefix directory is empty
+            let _ = fs::remove_dir(&prefix);
+        }
+
+        Ok(report)
+    }
+}
+
+fn link_or_copy(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::hard_link(from, to).is_err() {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA1: &str = "a9993e364706816aba3e25717850c26c9cd0d89d";
+    const OTHER_SHA1: &str = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+
+    #[test]
+    fn insert_and_link_into() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SharedStore::new(dir.path().join("store"));
+        let source = dir.path().join("a").join("abc.jar");
+        fs::create_dir_all(source.parent().unwrap()).unwrap();
+        fs::write(&source, b"abc").unwrap();
+
+        store.insert(&SHA1.to_ascii_uppercase(), &source).unwrap();
+        assert_eq!(store.object_path(SHA1), dir.path().join("store").join("a9").join(SHA1));
+        assert!(store.contains(SHA1, None));
+        assert!(store.contains(SHA1, Some(3)));
+        assert!(!store.contains(SHA1, Some(4)));
+        assert!(!store.contains(OTHER_SHA1, None));
+
+        // A second insert keeps the existing object
+        let other = dir.path().join("other.jar");
+        fs::write(&other, b"different").unwrap();
+        store.insert(SHA1, &other).unwrap();
+        assert_eq!(fs::read(store.object_path(SHA1)).unwrap(), b"abc");
+
+        // Linking replaces whatever is at the target
+        let target = dir.path().join("b").join("libraries").join("abc.jar");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::write(&target, b"stale").unwrap();
+        store.link_into(SHA1, &target).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn collect_garbage_keeps_referenced_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SharedStore::new(dir.path().join("store"));
+        for (sha1, contents) in [(SHA1, &b"abc"[..]), (OTHER_SHA1, &b""[..])] {
+            let source = dir.path().join(sha1);
+            fs::write(&source, contents).unwrap();
+            store.insert(sha1, &source).unwrap();
+        }
+        let kept_link = dir.path().join("kept.jar");
+        store.link_into(OTHER_SHA1, &kept_link).unwrap();
+
+        let report = store.collect_garbage(&HashSet::from([SHA1.to_string()])).unwrap();
+        assert_eq!((report.removed, report.removed_bytes, report.kept), (1, 0, 1));
+        assert!(store.contains(SHA1, Some(3)));
+        assert!(!store.contains(OTHER_SHA1, None));
+        // Emptied prefix directories go too, links in game directories stay
+        assert!(!store.object_path(OTHER_SHA1).parent().unwrap().exists());
+        assert!(kept_link.exists());
+    }
+
+    #[test]
+    fn collect_garbage_on_a_missing_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SharedStore::new(dir.path().join("store"));
+        let report = store.collect_garbage(&HashSet::new()).unwrap();
+        assert_eq!((report.removed, report.kept), (0, 0));
+    }
+}
@@ -0,0 +1,79 @@
+//! Helpers shared by the unit tests
+
+use crate::minecraft_api::RuleContext;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Local HTTP server answering each connection with the next canned response, then
+/// closing it. Request heads are recorded lowercased, in order.
+pub struct StubServer {
+    /// `http://127.0.0.1:<port>`
+    pub base: String,
+    /// A path under `base`; the server answers any path the same way
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubServer {
+    pub async fn start(responses: Vec<Vec<u8>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let url = format!("{}/file", base);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut head = Vec::new();
+                let mut buffer = [0; 1024];
+                while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+                    let n = socket.read(&mut buffer).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    head.extend_from_slice(&buffer[..n]);
+                }
+                recorded.lock().unwrap().push(String::from_utf8_lossy(&head).to_lowercase());
+                socket.write_all(&response).await.ok();
+                socket.shutdown().await.ok();
+            }
+        });
+
+        Self { base, url, requests }
+    }
+
+    /// Request heads received so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Raw HTTP response with a `Content-Length` matching `body`
+pub fn response(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    truncated_response(status, headers, body, body.len())
+}
+
+/// Raw HTTP response announcing `content_length` bytes but sending only `body`, like a
+/// connection dropped mid-stream
+pub fn truncated_response(status: &str, headers: &[(&str, &str)], body: &[u8], content_length: usize) -> Vec<u8> {
+    let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n", status, content_length);
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+
+    let mut response = response.into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Rule context for `os_name` on `os_arch`, with no OS version and no features enabled
+pub fn context(os_name: &str, os_arch: &str) -> RuleContext {
+    RuleContext {
+        os_name: os_name.to_string(),
+        os_arch: os_arch.to_string(),
+        ..Default::default()
+    }
+}
@@ -1,10 +1,20 @@
-import { createSignal } from "solid-js";
+import { createResource, createSignal, For, Show } from "solid-js";
 import toast from "solid-toast";
+import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import Folder from "../icons/Folder";
+import { ConfigIssue, JavaInstallation } from "../utils/types";
 export const ConfigPage = (props: { config: any; playerName: string; setPlayerName: (name: string) => void; onSave: (cfg: any) => void }) => {
     const [localName, setLocalName] = createSignal(props.playerName);
     const [localConfig, setLocalConfig] = createSignal({ ...props.config });
+    const [javaInstallations] = createResource(() => invoke<JavaInstallation[]>("get_java_installations"));
+    const [issues, setIssues] = createSignal<ConfigIssue[]>([]);
+
+    const issuesFor = (...fields: string[]) => (
+      <For each={issues().filter(issue => fields.some(field => issue.field === field || issue.field.startsWith(field + ".")))}>
+        {issue => <p class={"text-xs mt-1 " + (issue.warning ? "text-yellow-600" : "text-red-500")}>{issue.message}</p>}
+      </For>
+    );
 
     const isDisabled = () => {
         if (localConfig().max_memory < localConfig().min_memory) return true;
@@ -23,6 +33,17 @@ export const ConfigPage = (props: { config: any; playerName: string; setPlayerNa
             value={localName()}
             onInput={e => setLocalName((e.target as HTMLInputElement).value)}
           />
+          {issuesFor("player_name")}
+        </div>
+        <div class="mb-4">
+          <label class="block text-sm font-semibold mb-1 text-gray-700 dark:text-gray-200">Custom UUID</label>
+          <input
+            class="w-full px-3 py-2 rounded-lg border border-gray-300 dark:border-gray-700 bg-gray-50 dark:bg-gray-800 text-gray-900 dark:text-gray-100"
+            value={localConfig().player_uuid || ""}
+            onInput={e => setLocalConfig({ ...localConfig(), player_uuid: (e.target as HTMLInputElement).value || null })}
+            placeholder="Derived from the player name"
+          />
+          {issuesFor("player_uuid")}
         </div>
         <div class="mb-4">
           <label class="block text-sm font-semibold mb-1 text-gray-700 dark:text-gray-200">Game Directory</label>
@@ -47,6 +68,7 @@ export const ConfigPage = (props: { config: any; playerName: string; setPlayerNa
             <Folder />
           </button>
           </div>
+          {issuesFor("game_directory")}
         </div>
         <div class="mb-4 flex gap-4">
           <div class="flex-1">
@@ -70,6 +92,28 @@ export const ConfigPage = (props: { config: any; playerName: string; setPlayerNa
             />
           </div>
         </div>
+        {issuesFor("memory", "max_memory")}
+        <div class="mb-4">
+          <label class="block text-sm font-semibold mb-1 text-gray-700 dark:text-gray-200">Java Runtime</label>
+          <select
+            class="w-full px-3 py-2 rounded-lg border border-gray-300 dark:border-gray-700 bg-gray-50 dark:bg-gray-800 text-gray-900 dark:text-gray-100"
+            value={localConfig().java_executable || ""}
+            onChange={e => setLocalConfig({ ...localConfig(), java_executable: e.currentTarget.value || null })}
+          >
+            <option value="">{javaInstallations.loading ? "Searching for Java..." : "Automatic"}</option>
+            <For each={javaInstallations() || []}>
+              {java => (
+                <option value={java.path}>
+                  Java {java.version} ({[java.vendor, java.arch].filter(Boolean).join(", ")}) - {java.path}
+                </option>
+              )}
+            </For>
+            <Show when={localConfig().java_executable && !(javaInstallations() || []).some(java => java.path === localConfig().java_executable)}>
+              <option value={localConfig().java_executable}>{localConfig().java_executable}</option>
+            </Show>
+          </select>
+          {issuesFor("java_executable", "java_executables")}
+        </div>
         <div class="mb-4">
           <label class="block text-sm font-semibold mb-1 text-gray-700 dark:text-gray-200">JVM Arguments</label>
           <input
@@ -91,9 +135,16 @@ export const ConfigPage = (props: { config: any; playerName: string; setPlayerNa
           <button
             disabled={isDisabled()}
             class="px-6 py-2 rounded-lg bg-green-500 hover:bg-green-600 text-white font-bold shadow transition"
-            onClick={() => {
+            onClick={async () => {
+              const config = { ...localConfig(), player_name: localName() };
+              const found = await invoke<ConfigIssue[]>("validate_config", { config });
+              setIssues(found);
+              if (found.some(issue => !issue.warning)) {
+                toast.error("Please fix the highlighted settings");
+                return;
+              }
               props.setPlayerName(localName());
-              props.onSave({ ...localConfig(), player_name: localName() });
+              props.onSave(config);
               toast("Settings saved successfully!");
             }}
           >
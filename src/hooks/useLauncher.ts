@@ -1,12 +1,13 @@
 import { createSignal, onMount } from "solid-js";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { Version } from "../utils/types";
+import { DownloadProgress, Version } from "../utils/types";
 import { APP_CONSTANTS } from "../constants";
 
 export const useLauncher = () => {
   const [logs, setLogs] = createSignal<string[]>([]);
   const [progress, setProgress] = createSignal(0);
+  const [downloadProgress, setDownloadProgress] = createSignal<DownloadProgress | null>(null);
   const [showAll, setShowAll] = createSignal(false);
   const [selectedVersion, setSelectedVersion] = createSignal<string>(APP_CONSTANTS.DEFAULT_VERSION);
   const [showVersionSelector, setShowVersionSelector] = createSignal(false);
@@ -73,10 +74,17 @@ export const useLauncher = () => {
     setProgress(event.payload as number);
   });
 
+  listen<DownloadProgress>("download_progress", (event) => {
+    const p = event.payload;
+    setDownloadProgress(p);
+    setProgress(p.bytes_total > 0 ? Math.min(p.bytes_done / p.bytes_total, 1) : 1);
+  });
+
   return {
     // State
     logs,
     progress,
+    downloadProgress,
     showAll,
     selectedVersion,
     showVersionSelector,
@@ -3,17 +3,69 @@ export interface Version {
     id: string;
     type: string;
     url: string;
+    sha1?: string;
+    time?: string;
+    releaseTime?: string;
+    complianceLevel?: number;
+    origin?: VersionOrigin;
 }
 
+export type VersionOrigin = "remote" | "local" | "both";
+
 // Launcher configuration types
 export interface LauncherConfig {
     player_name: string;
     player_uuid?: string;
     game_directory: string;
     java_executable?: string;
+    java_executables?: Record<string, string>;
     jvm_args?: string[];
     memory_max?: number;
     memory_min?: number;
+    mirrors?: Partial<Record<Endpoint, string[]>>;
+}
+
+export type Endpoint =
+    | "mojang_meta"
+    | "mojang_data"
+    | "mojang_libraries"
+    | "mojang_resources"
+    | "fabric_meta"
+    | "fabric_maven"
+    | "modrinth";
+
+// Result of the verify_game_files command
+export interface VerificationReport {
+    checked: number;
+    broken: string[];
+    trusted: string[];
+    repaired: string[];
+    failed: string[];
+}
+
+// Result of the gc_shared_store command
+export interface StoreGcReport {
+    removed: number;
+    removed_bytes: number;
+    kept: number;
+}
+
+// Entry of the validate_config result, tied to the config field it concerns
+export interface ConfigIssue {
+    field: string;
+    message: string;
+    warning: boolean;
+}
+
+// Entry of the get_java_installations command
+export interface JavaInstallation {
+    path: string;
+    version: string;
+    major_version: number;
+    vendor?: string;
+    arch?: string;
+    bits?: number;
+    source: "config" | "java_home" | "path" | "system" | "sdkman" | "asdf" | "jenv" | "launcher";
 }
 
 // Application state types
@@ -38,6 +90,19 @@ export interface ProgressEvent {
     payload: number;
 }
 
+// Payload of the download_progress event, aggregated over the whole launch preparation
+export interface DownloadProgress {
+    file: string;
+    file_bytes: number;
+    file_total?: number;
+    bytes_done: number;
+    bytes_total: number;
+    files_done: number;
+    files_total: number;
+    bytes_per_second: number;
+    eta_seconds?: number;
+}
+
 // Component prop types
 export interface SidebarProps {
     activeSection: string;